@@ -0,0 +1,191 @@
+//////////////////////////////////////////////////////////////////////////
+//                                                                      //
+// Rusty Yacht - A yatzy game for the terminal               R U S T Y  //
+// Copyright (C) 2019  Albin Söderqvist <albin@fripost.org>  U       A  //
+//                                                           S       C  //
+// This game is free software: you can redistribute it       T       H  //
+// and/or modify it under the terms of GNU General Public    Y A C H T  //
+// License as published by the Free Software Foundation,                //
+// either version 3 of the License, or (at your option)                 //
+// any later version.                                                   //
+//                                                                      //
+// Rusty Yacht is distributed in the hope that it will be fun to play,  //
+// but WITHOUT ANY WARRANTY; without even the implied warranty of       //
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the         //
+// GNU General Public License for more details.                         //
+//                                                                      //
+// You should have received a copy of the GNU General Public License    //
+// along with the game. If not, see <https://www.gnu.org/licenses/>.    //
+//                                                                      //
+//////////////////////////////////////////////////////////////////////////
+
+//! Review of a finished game. Every decision is replayed and compared with
+//! optimal play, measured in expected final score.
+
+use solver::Solver;
+use {Category, GameState, Move};
+
+/// What was done with the dice.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Choice {
+    /// Kept these dice and rerolled the rest. Keeping all of them means
+    /// not rolling again.
+    Keep(Vec<usize>),
+    /// Scored in a slot, or struck it out if there are no points.
+    Slot(Category, Option<u16>),
+}
+
+/// A decision of a player next to the best one.
+#[derive(Debug, Clone)]
+pub struct Decision {
+    /// Index of the player in the game.
+    pub player: usize,
+    /// Which of the player's turns, counting from 1.
+    pub turn: usize,
+    /// How many times the dice had been rolled.
+    pub roll: u8,
+    pub dice: Vec<usize>,
+    pub chosen: Choice,
+    /// Expected final score after the choice.
+    pub expected: f64,
+    pub best: Choice,
+    /// Expected final score after the best choice.
+    pub best_expected: f64,
+}
+
+impl Decision {
+    /// Expected points given up by the choice.
+    pub fn loss(&self) -> f64 {
+        self.best_expected - self.expected
+    }
+}
+
+/// Replays the history of `game` and rates every decision, or returns
+/// `None` if the solver cannot play the game or the history does not
+/// replay.
+pub fn analyze(game: &GameState) -> Option<Vec<Decision>> {
+    if game.is_forced() {
+        return None;
+    }
+    let solver = Solver::for_ruleset(game.ruleset())?;
+    rate(game, game.rematch(), solver)
+}
+
+/// Rates every decision in the history of `game`, making the moves again
+/// in `replay`, a new game with the same players.
+fn rate(game: &GameState, mut replay: GameState, solver: &Solver) -> Option<Vec<Decision>> {
+    let mut turns = vec![1; game.players().len()];
+    let mut decisions = Vec::new();
+
+    for step in game.history() {
+        let player = replay.current_player();
+        let decision = |chosen, expected, best, best_expected| Decision {
+            player,
+            turn: turns[player],
+            roll: replay.rolls(),
+            dice: replay.dice().current.clone(),
+            chosen,
+            expected,
+            best,
+            best_expected,
+        };
+        let rolled = replay.rolls() > 0;
+        match *step {
+            Move::Roll { ref kept, .. } if rolled => {
+                decisions.push(rate_keep(solver, &replay, kept, &decision));
+            },
+            Move::Place(category) | Move::Strike(category) => {
+                if replay.rolls_left() > 0 {
+                    let kept = vec![true; replay.dice().current.len()];
+                    decisions.push(rate_keep(solver, &replay, &kept, &decision));
+                }
+                let slots = solver.slot_values(replay.score(), &replay.dice().current);
+                let slot = |category: Category| Choice::Slot(category, replay.points(category));
+                let (chosen, expected) = match *step {
+                    Move::Strike(_) => (Choice::Slot(category, None), solver.strike_value(replay.score(), category)),
+                    _ => {
+                        let expected = slots.iter()
+                            .find(|&&(other, _)| other == category)
+                            .map_or(0.0, |&(_, expected)| expected);
+                        (slot(category), expected)
+                    },
+                };
+                decisions.push(decision(chosen, expected, slot(slots[0].0), slots[0].1));
+                turns[player] += 1;
+            },
+            _ => (),
+        }
+        replay.apply(step).ok()?;
+    }
+    Some(decisions)
+}
+
+/// Rates keeping the `kept` dice before the next roll.
+fn rate_keep(solver: &Solver, game: &GameState, kept: &[bool],
+             decision: &dyn Fn(Choice, f64, Choice, f64) -> Decision) -> Decision {
+    let dice = &game.dice().current;
+    let values = solver.keep_values(game.score(), dice, game.rolls_left());
+    let chosen = kept_dice(dice, kept);
+    let expected = values.iter()
+        .find(|&(keep, _)| kept_dice(dice, keep) == chosen)
+        .map_or(0.0, |&(_, expected)| expected);
+    let (ref best, best_expected) = values[0];
+    decision(Choice::Keep(chosen), expected, Choice::Keep(kept_dice(dice, best)), best_expected)
+}
+
+fn kept_dice(dice: &[usize], kept: &[bool]) -> Vec<usize> {
+    let mut kept: Vec<usize> = (0..dice.len()).filter(|&i| kept[i]).map(|i| dice[i]).collect();
+    kept.sort();
+    kept
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {Ruleset, Validator, Yatzy};
+
+    /// Yatzy cut down to Chance and Yatzy, small enough to solve in a test.
+    struct Short;
+
+    impl Ruleset for Short {
+        fn name(&self) -> &'static str {
+            "Short"
+        }
+
+        fn categories(&self) -> &'static [Category] {
+            &[Category::Chance, Category::Yatzy]
+        }
+
+        fn validator(&self, category: Category) -> Validator {
+            Yatzy.validator(category)
+        }
+    }
+
+    fn roll(game: &mut GameState, kept: &[bool], dice: &[usize]) {
+        game.apply(&Move::Roll { kept: kept.to_vec(), dice: dice.to_vec() }).unwrap();
+    }
+
+    #[test]
+    fn rates_keeps_and_strikes() {
+        let solver = Solver::new(&Short);
+        let mut game = GameState::with_ruleset(Box::new(Short));
+        roll(&mut game, &[false; 5], &[6, 6, 6, 6, 1]);
+        // Rerolling the sixes is a poor keep.
+        roll(&mut game, &[false, false, false, false, true], &[2, 3, 4, 5, 1]);
+        game.strike(Category::Chance).unwrap();
+
+        let decisions = rate(&game, GameState::with_ruleset(Box::new(Short)), &solver).unwrap();
+        assert_eq!(decisions.len(), 3);
+        assert_eq!(decisions[0].chosen, Choice::Keep(vec![1]));
+        assert_eq!(decisions[0].best, Choice::Keep(vec![6, 6, 6, 6]));
+        assert!(decisions[0].loss() > 0.0);
+
+        // Striking out Chance gives up the 15 points the dice show there,
+        // while striking out Yatzy would only give up a long shot.
+        let strike = &decisions[2];
+        assert_eq!(strike.chosen, Choice::Slot(Category::Chance, None));
+        assert!((strike.expected - 50.0 * 0.046029).abs() < 1e-3);
+        assert_eq!(strike.best, Choice::Slot(Category::Yatzy, None));
+        assert!((strike.best_expected - 70.0 / 3.0).abs() < 1e-3);
+    }
+}
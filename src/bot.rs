@@ -0,0 +1,279 @@
+//////////////////////////////////////////////////////////////////////////
+//                                                                      //
+// Rusty Yacht - A yatzy game for the terminal               R U S T Y  //
+// Copyright (C) 2019  Albin Söderqvist <albin@fripost.org>  U       A  //
+//                                                           S       C  //
+// This game is free software: you can redistribute it       T       H  //
+// and/or modify it under the terms of GNU General Public    Y A C H T  //
+// License as published by the Free Software Foundation,                //
+// either version 3 of the License, or (at your option)                 //
+// any later version.                                                   //
+//                                                                      //
+// Rusty Yacht is distributed in the hope that it will be fun to play,  //
+// but WITHOUT ANY WARRANTY; without even the implied warranty of       //
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the         //
+// GNU General Public License for more details.                         //
+//                                                                      //
+// You should have received a copy of the GNU General Public License    //
+// along with the game. If not, see <https://www.gnu.org/licenses/>.    //
+//                                                                      //
+//////////////////////////////////////////////////////////////////////////
+
+//! Computer opponents. A bot looks at the game through the same
+//! `GameState` a human plays and only makes the two decisions of a turn:
+//! which dice to keep and which slot to use.
+
+use std::collections::HashMap;
+
+use rand::{Rng, RngCore};
+
+use solver::Solver;
+use {outcomes, Category, GameState, SlotState};
+
+/// How a computer player decides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Keeps dice and picks slots at random.
+    Random,
+    /// Goes for the most points in a single slot this turn.
+    Greedy,
+    /// Like greedy, but weighs upper section scores against what is needed
+    /// for the bonus and prefers to strike out cheap slots.
+    Heuristic,
+    /// Plays for the highest expected total using the solver, and like
+    /// the heuristic bot where the solver does not apply.
+    Optimal,
+}
+
+impl Strategy {
+    pub const ALL: [Strategy; 4] = [Strategy::Random, Strategy::Greedy, Strategy::Heuristic,
+                                    Strategy::Optimal];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Strategy::Random => "Random",
+            Strategy::Greedy => "Greedy",
+            Strategy::Heuristic => "Heuristic",
+            Strategy::Optimal => "Optimal",
+        }
+    }
+
+    /// Looks up a strategy by name, ignoring case.
+    pub fn by_name(name: &str) -> Option<Strategy> {
+        Strategy::ALL.iter()
+            .cloned()
+            .find(|strategy| strategy.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Which of the current dice to keep before the next roll. Keeping all
+    /// of them means the bot is done rolling. The random bot draws from
+    /// `rng`, which should be the game's `turn_rng`.
+    pub fn keep(self, game: &GameState, rng: &mut dyn RngCore) -> Vec<bool> {
+        let current = &game.dice().current;
+        if self == Strategy::Random {
+            return current.iter().map(|_| rng.gen()).collect();
+        }
+        if let Some(solver) = self.solver(game) {
+            return solver.keep_values(game.score(), current, game.rolls_left()).remove(0).0;
+        }
+
+        // Try every set of dice to keep, starting with all of them, and
+        // pick the one with the best expected value after the reroll.
+        let all = (1 << current.len()) - 1;
+        let mut seen: HashMap<Vec<usize>, f64> = HashMap::new();
+        let mut best = (all, self.best_value(game, current));
+        for mask in (0..all).rev() {
+            let mut kept: Vec<usize> = (0..current.len())
+                .filter(|i| mask & (1 << i) != 0)
+                .map(|i| current[i])
+                .collect();
+            kept.sort();
+            let value = *seen.entry(kept.clone()).or_insert_with(|| {
+                outcomes(current.len() - kept.len()).iter()
+                    .map(|roll| {
+                        let mut dice = kept.clone();
+                        dice.extend(roll);
+                        probability(roll) * self.best_value(game, &dice)
+                    })
+                    .sum()
+            });
+            if value > best.1 {
+                best = (mask, value);
+            }
+        }
+        (0..current.len()).map(|i| best.0 & (1 << i) != 0).collect()
+    }
+
+    /// The slot to score the current dice in, or strike out if they do not
+    /// qualify for it. The random bot draws from `rng`, as in `keep`.
+    pub fn choose(self, game: &GameState, rng: &mut dyn RngCore) -> Category {
+        let current = &game.dice().current;
+        let candidates = candidates(game, current);
+        if self == Strategy::Random {
+            return candidates[rng.gen_range(0, candidates.len())];
+        }
+        if let Some(solver) = self.solver(game) {
+            return solver.slot_values(game.score(), current)[0].0;
+        }
+        let mut best = candidates[0];
+        for &category in &candidates[1..] {
+            if self.value(game, category, current) > self.value(game, best, current) {
+                best = category;
+            }
+        }
+        best
+    }
+
+    /// Plays the rest of the current player's turn without showing it. The
+    /// game must not be over.
+    pub fn play_turn(self, game: &mut GameState) {
+        let mut rng = game.turn_rng();
+        if game.rolls() == 0 {
+            // The first roll of a turn is always there in a game not over.
+            game.roll().unwrap();
+        }
+        while game.rolls_left() > 0 {
+            let keep = self.keep(game, &mut rng);
+            if keep.iter().all(|&kept| kept) {
+                break;
+            }
+            for (i, &kept) in keep.iter().enumerate() {
+                if kept != (game.dice().to_keep[i] != 0) {
+                    // The dice have been rolled and `keep` has one per die.
+                    game.toggle_keep(i).unwrap();
+                }
+            }
+            // Checked above that there are rolls left.
+            game.roll().unwrap();
+        }
+        let category = self.choose(game, &mut rng);
+        if game.place(category).is_err() {
+            // `choose` picks an open slot the dice may go in, which can be
+            // struck out if they do not qualify for it.
+            game.strike(category).unwrap();
+        }
+    }
+
+    /// The solver, for the optimal bot if it can play this game.
+    fn solver(self, game: &GameState) -> Option<&'static Solver> {
+        if self != Strategy::Optimal || game.is_forced() {
+            return None;
+        }
+        Solver::for_ruleset(game.ruleset())
+    }
+
+    fn best_value(self, game: &GameState, dice: &[usize]) -> f64 {
+        candidates(game, dice).into_iter()
+            .map(|category| self.value(game, category, dice))
+            .fold(f64::MIN, f64::max)
+    }
+
+    /// What scoring `dice` in `category` is worth to this strategy.
+    fn value(self, game: &GameState, category: Category, dice: &[usize]) -> f64 {
+        let ruleset = game.ruleset();
+        let score = game.score();
+        let points = ruleset.points(score, category, dice).unwrap_or(0) as f64;
+        if self == Strategy::Greedy {
+            return points;
+        }
+        if category.is_upper() && score.sum() < ruleset.bonus_threshold() {
+            // Par is the share of the bonus threshold that falls on this
+            // face, e.g. three of each with five dice.
+            // Every upper slot has a face.
+            let face = category.face().unwrap();
+            let par = (face * ruleset.bonus_threshold() as usize) as f64 / 21.0;
+            let weight = ruleset.bonus() as f64 / ruleset.bonus_threshold() as f64;
+            return points + weight * (points - par);
+        }
+        if points == 0.0 {
+            return -strike_cost(category);
+        }
+        points
+    }
+}
+
+/// The open slots the dice may go in.
+fn candidates(game: &GameState, dice: &[usize]) -> Vec<Category> {
+    if game.is_forced() {
+        return game.next_slot().into_iter().collect();
+    }
+    let score = game.score();
+    game.ruleset().categories().iter()
+        .cloned()
+        .filter(|&category| score.get(category) == Some(SlotState::Empty))
+        .filter(|&category| game.ruleset().allowed(score, category, dice))
+        .collect()
+}
+
+/// A rough price of giving up a lower section slot, so that rare and
+/// valuable combinations are kept open the longest.
+fn strike_cost(category: Category) -> f64 {
+    match category {
+        Category::Yatzy | Category::FiveOfAKind => 10.0,
+        Category::LargeStraight | Category::FullStraight => 6.0,
+        Category::SmallStraight | Category::FourOfAKind | Category::Tower => 5.0,
+        Category::FullHouse | Category::Castle | Category::ThreePairs => 4.0,
+        Category::Chance => 8.0,
+        _ => 2.0,
+    }
+}
+
+/// The chance of rolling exactly `roll`, in any order.
+fn probability(roll: &[usize]) -> f64 {
+    let mut orders = factorial(roll.len());
+    for face in 1..7 {
+        orders /= factorial(roll.iter().filter(|&&die| die == face).count());
+    }
+    orders as f64 / 6f64.powi(roll.len() as i32)
+}
+
+fn factorial(n: usize) -> usize {
+    (1..=n).product()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {Move, Yatzy};
+
+    /// A solitaire game of `strategy` from `seed`, played to the end.
+    fn play(strategy: Strategy, seed: u64) -> GameState {
+        let mut game = GameState::with_seats(Box::new(Yatzy), vec![(String::new(), Some(strategy))]);
+        game.set_seed(seed);
+        while !game.is_over() {
+            strategy.play_turn(&mut game);
+        }
+        game
+    }
+
+    #[test]
+    fn same_seed_plays_the_same_game() {
+        for &strategy in &[Strategy::Random, Strategy::Greedy, Strategy::Heuristic] {
+            let first = play(strategy, 1);
+            let second = play(strategy, 1);
+            assert_eq!(first.history(), second.history(), "{} bot", strategy.name());
+            assert_eq!(first.score().total(), second.score().total());
+        }
+        assert_ne!(play(Strategy::Random, 1).history(), play(Strategy::Random, 2).history());
+    }
+
+    #[test]
+    fn greedy_takes_the_most_points() {
+        let mut game = GameState::with_seats(Box::new(Yatzy), vec![(String::new(), Some(Strategy::Greedy))]);
+        game.apply(&Move::Roll { kept: vec![false; 5], dice: vec![6, 6, 6, 6, 6] }).unwrap();
+        let mut rng = game.turn_rng();
+        assert_eq!(Strategy::Greedy.keep(&game, &mut rng), vec![true; 5]);
+        assert_eq!(Strategy::Greedy.choose(&game, &mut rng), Category::Yatzy);
+    }
+
+    #[test]
+    fn bots_only_pick_slots_they_may_use() {
+        let mut game = GameState::with_seats(Box::new(Yatzy), vec![(String::new(), Some(Strategy::Random))]);
+        game.set_forced(true);
+        game.apply(&Move::Roll { kept: vec![false; 5], dice: vec![6, 6, 6, 6, 6] }).unwrap();
+        let mut rng = game.turn_rng();
+        for &strategy in &Strategy::ALL {
+            assert_eq!(strategy.choose(&game, &mut rng), Category::Ones);
+        }
+    }
+}
@@ -0,0 +1,414 @@
+//////////////////////////////////////////////////////////////////////////
+//                                                                      //
+// Rusty Yacht - A yatzy game for the terminal               R U S T Y  //
+// Copyright (C) 2019  Albin Söderqvist <albin@fripost.org>  U       A  //
+//                                                           S       C  //
+// This game is free software: you can redistribute it       T       H  //
+// and/or modify it under the terms of GNU General Public    Y A C H T  //
+// License as published by the Free Software Foundation,                //
+// either version 3 of the License, or (at your option)                 //
+// any later version.                                                   //
+//                                                                      //
+// Rusty Yacht is distributed in the hope that it will be fun to play,  //
+// but WITHOUT ANY WARRANTY; without even the implied warranty of       //
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the         //
+// GNU General Public License for more details.                         //
+//                                                                      //
+// You should have received a copy of the GNU General Public License    //
+// along with the game. If not, see <https://www.gnu.org/licenses/>.    //
+//                                                                      //
+//////////////////////////////////////////////////////////////////////////
+
+//! The settings in ~/.config/rusty-yacht/config.toml. Every setting has a
+//! default, so the file only needs the ones that differ, and it does not
+//! need to be there at all.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use termion::event::Key;
+use toml::Value;
+use toml::value::Table;
+
+use keys::{self, Action, KeyName, Keymap};
+use theme::{self, Theme};
+use {Error, config_dir, ruleset_by_name, rulesets};
+
+/// The config file with every setting at its default, as printed by
+/// `rusty-yacht config --print-default`.
+pub const DEFAULT: &str = "\
+# Rusty Yacht configuration. Settings left out keep these defaults.
+
+[game]
+# The name to put solitaire scores on the high-score table under.
+# Leave it empty to be asked after each game.
+name = \"\"
+# The ruleset to play when --ruleset is not given: yatzy, yahtzee or
+# maxi-yatzy.
+ruleset = \"yatzy\"
+# How many actions can be taken back when --undo is not given. Games
+# with undo do not go on the high-score table.
+undo = 0
+
+[display]
+# How fast computer players move: 2.0 is twice as fast and 0 shows
+# only the result of each turn.
+animation_speed = 1.0
+# The colors: default, high-contrast, solarized, colorblind, or none
+# for no colors. There are no colors either when NO_COLOR is set or the
+# terminal can't show them.
+theme = \"default\"
+# Show the chance of filling each open slot while picking dice.
+odds = true
+
+[keys]
+# The keys to start from: default has the arrow keys, vim adds h, j, k
+# and l with ? for hints, and numbers adds 1 to 6 to keep a die without
+# moving to it.
+preset = \"default\"
+# Any action can have keys of its own instead, as a name or a list:
+# left, right, up, down, toggle, die-1 to die-6, roll, score, strike,
+# hint and undo. Keys are single characters or Enter, Space, Tab,
+# Backspace, Delete, Insert, Home, End, PageUp, PageDown, Left, Right,
+# Up and Down.
+# toggle = [\"Space\", \"x\"]
+
+[prompts]
+# Wait for Enter after each slot is used.
+confirm_placement = true
+# Press - twice to strike out a slot.
+confirm_strike = false
+";
+
+/// The sections of the config file and the settings in each.
+const SETTINGS: &[(&str, &[&str])] = &[
+    ("game", &["name", "ruleset", "undo"]),
+    ("display", &["animation_speed", "theme", "odds"]),
+    ("keys", &["preset", "left", "right", "up", "down", "toggle", "die-1", "die-2", "die-3",
+               "die-4", "die-5", "die-6", "roll", "score", "strike", "hint", "undo"]),
+    ("prompts", &["confirm_placement", "confirm_strike"]),
+];
+
+/// The longest name that fits on the high-score table.
+const MAX_NAME: usize = 24;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// The name to log solitaire scores under, instead of asking.
+    pub name: Option<String>,
+    /// The id of the ruleset to play when none is given.
+    pub ruleset: String,
+    /// How many actions can be undone when --undo is not given.
+    pub undo: usize,
+    /// How fast computer players move; 1.0 is normal and 0.0 does not
+    /// pause at all.
+    pub animation_speed: f64,
+    /// The colors to use, or none.
+    pub theme: Option<Theme>,
+    /// Whether to show the chance of filling each slot while picking dice.
+    pub odds: bool,
+    /// What the keys do while picking dice and slots.
+    pub keys: Keymap,
+    /// Whether to wait for Enter after a slot is used.
+    pub confirm_placement: bool,
+    /// Whether striking out a slot takes pressing - twice.
+    pub confirm_strike: bool,
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// The settings in use, the defaults unless `set` was called.
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+/// Makes `config` the settings in use. Only the first call counts.
+pub fn set(config: Config) {
+    let _ = CONFIG.set(config);
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            name: None,
+            ruleset: String::from("yatzy"),
+            undo: 0,
+            animation_speed: 1.0,
+            theme: Some(theme::THEMES[0]),
+            odds: true,
+            keys: Keymap::default(),
+            confirm_placement: true,
+            confirm_strike: false,
+        }
+    }
+}
+
+impl Config {
+    /// Where the config file is kept, ~/.config/rusty-yacht/config.toml.
+    pub fn path() -> Result<PathBuf, Error> {
+        Ok(config_dir()?.join("config.toml"))
+    }
+
+    /// Reads the settings at `path`, or the defaults if there is no file.
+    pub fn load(path: &Path) -> Result<Config, Error> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(e) => return Err(e.into()),
+        };
+        Config::parse(&text).map_err(|problems| Error::Config(path.to_path_buf(), problems))
+    }
+
+    /// Reads the settings in the text of a config file, or lists everything
+    /// that is wrong with it.
+    pub fn parse(text: &str) -> Result<Config, Vec<String>> {
+        let table: Table = toml::from_str(text).map_err(|e| vec![e.to_string()])?;
+        let mut reader = Reader { table, problems: Vec::new() };
+        reader.check_names();
+
+        let mut config = Config::default();
+        if let Some(name) = reader.string("game", "name") {
+            if name.chars().count() > MAX_NAME {
+                reader.problem("game", "name", &format!("can be at most {} characters", MAX_NAME));
+            } else if name.chars().any(char::is_control) {
+                reader.problem("game", "name", "can't have control characters");
+            } else if !name.trim().is_empty() {
+                config.name = Some(name.trim().to_string());
+            }
+        }
+        if let Some(name) = reader.string("game", "ruleset") {
+            match ruleset_by_name(&name) {
+                Some(ruleset) => config.ruleset = ruleset.id(),
+                None => {
+                    let ids: Vec<_> = rulesets().iter().map(|ruleset| ruleset.id()).collect();
+                    reader.problem("game", "ruleset", &format!("'{}' is not one of: {}", name, ids.join(", ")));
+                },
+            }
+        }
+        if let Some(undo) = reader.integer("game", "undo") {
+            if undo < 0 {
+                reader.problem("game", "undo", "can't be negative");
+            } else {
+                config.undo = undo as usize;
+            }
+        }
+        if let Some(speed) = reader.number("display", "animation_speed") {
+            if !speed.is_finite() || speed < 0.0 {
+                reader.problem("display", "animation_speed", "needs to be 0 or more");
+            } else {
+                config.animation_speed = speed;
+            }
+        }
+        if let Some(name) = reader.string("display", "theme") {
+            match Theme::by_name(&name) {
+                Some(theme) => config.theme = Some(theme),
+                None if name.eq_ignore_ascii_case("none") => config.theme = None,
+                None => {
+                    let names: Vec<_> = theme::THEMES.iter().map(|theme| theme.name).collect();
+                    reader.problem("display", "theme", &format!("'{}' is not one of: {}, none", name, names.join(", ")));
+                },
+            }
+        }
+        if let Some(odds) = reader.boolean("display", "odds") {
+            config.odds = odds;
+        }
+        if let Some(name) = reader.string("keys", "preset") {
+            match Keymap::preset(&name) {
+                Some(keymap) => config.keys = keymap,
+                None => reader.problem("keys", "preset", &format!("'{}' is not one of: {}", name,
+                                                                  Keymap::PRESETS.join(", "))),
+            }
+        }
+        for action in Action::all() {
+            if let Some(keys) = reader.keys("keys", &action.name()) {
+                config.keys.bind(action, keys);
+            }
+        }
+        for &action in &[Action::Left, Action::Right, Action::Toggle, Action::Roll, Action::Score, Action::Strike] {
+            if config.keys.keys(action).is_empty() {
+                reader.problems.push(format!("[keys] {} needs at least one key", action.name()));
+            }
+        }
+        for (first, second, key) in config.keys.conflicts() {
+            reader.problems.push(format!("[keys] {} is bound to both {} and {}", KeyName(key),
+                                         first.name(), second.name()));
+        }
+        if let Some(confirm) = reader.boolean("prompts", "confirm_placement") {
+            config.confirm_placement = confirm;
+        }
+        if let Some(confirm) = reader.boolean("prompts", "confirm_strike") {
+            config.confirm_strike = confirm;
+        }
+
+        if reader.problems.is_empty() { Ok(config) } else { Err(reader.problems) }
+    }
+
+    /// How long to show a step of a computer player's turn that takes
+    /// `millis` milliseconds at normal speed.
+    pub fn bot_pause(&self, millis: u64) -> Duration {
+        if self.animation_speed == 0.0 {
+            return Duration::from_millis(0);
+        }
+        Duration::from_millis((millis as f64 / self.animation_speed) as u64)
+    }
+}
+
+/// Looks up settings in a parsed config file, noting what is wrong with
+/// them as it goes.
+struct Reader {
+    table: Table,
+    problems: Vec<String>,
+}
+
+impl Reader {
+    /// Notes every section and setting that the game does not know of.
+    fn check_names(&mut self) {
+        let mut problems = Vec::new();
+        for (section, value) in &self.table {
+            let keys = match SETTINGS.iter().find(|&&(name, _)| name == section) {
+                Some(&(_, keys)) => keys,
+                None => {
+                    problems.push(format!("unknown section [{}]", section));
+                    continue;
+                },
+            };
+            match *value {
+                Value::Table(ref settings) => for key in settings.keys() {
+                    if !keys.contains(&key.as_str()) {
+                        problems.push(format!("unknown setting '{}' in [{}]", key, section));
+                    }
+                },
+                _ => problems.push(format!("[{}] needs to be a section", section)),
+            }
+        }
+        self.problems.extend(problems);
+    }
+
+    fn problem(&mut self, section: &str, key: &str, problem: &str) {
+        self.problems.push(format!("[{}] {}: {}", section, key, problem));
+    }
+
+    fn get(&self, section: &str, key: &str) -> Option<&Value> {
+        self.table.get(section)?.as_table()?.get(key)
+    }
+
+    fn string(&mut self, section: &str, key: &str) -> Option<String> {
+        let value = self.get(section, key)?.as_str().map(str::to_string);
+        if value.is_none() {
+            self.problem(section, key, "needs to be a string in quotes");
+        }
+        value
+    }
+
+    fn integer(&mut self, section: &str, key: &str) -> Option<i64> {
+        let value = self.get(section, key)?.as_integer();
+        if value.is_none() {
+            self.problem(section, key, "needs to be a whole number");
+        }
+        value
+    }
+
+    /// A number with or without decimals.
+    fn number(&mut self, section: &str, key: &str) -> Option<f64> {
+        let value = match *self.get(section, key)? {
+            Value::Float(number) => Some(number),
+            Value::Integer(number) => Some(number as f64),
+            _ => None,
+        };
+        if value.is_none() {
+            self.problem(section, key, "needs to be a number");
+        }
+        value
+    }
+
+    /// A key, or a list of them.
+    fn keys(&mut self, section: &str, key: &str) -> Option<Vec<Key>> {
+        let names: Option<Vec<String>> = match *self.get(section, key)? {
+            Value::String(ref name) => Some(vec![name.clone()]),
+            Value::Array(ref names) => names.iter().map(|name| name.as_str().map(str::to_string)).collect(),
+            _ => None,
+        };
+        let names = match names {
+            Some(names) => names,
+            None => {
+                self.problem(section, key, "needs to be a key name in quotes, or a list of them");
+                return None;
+            },
+        };
+        let mut keys = Vec::new();
+        for name in names {
+            match keys::parse_key(&name) {
+                Some(parsed) => keys.push(parsed),
+                None => {
+                    self.problem(section, key, &format!("there is no key called '{}'", name));
+                    return None;
+                },
+            }
+        }
+        Some(keys)
+    }
+
+    fn boolean(&mut self, section: &str, key: &str) -> Option<bool> {
+        let value = self.get(section, key)?.as_bool();
+        if value.is_none() {
+            self.problem(section, key, "needs to be true or false");
+        }
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_file_gives_the_defaults() {
+        assert_eq!(Config::parse(DEFAULT), Ok(Config::default()));
+        assert_eq!(Config::parse(""), Ok(Config::default()));
+    }
+
+    #[test]
+    fn settings_are_read() {
+        let config = Config::parse("[game]\nname = \" Albin \"\nruleset = \"Yahtzee\"\nundo = 3\n\
+                                    [display]\nanimation_speed = 2\ntheme = \"none\"\n").unwrap();
+        assert_eq!(config.name, Some(String::from("Albin")));
+        assert_eq!(config.ruleset, "yahtzee");
+        assert_eq!(config.undo, 3);
+        assert_eq!(config.animation_speed, 2.0);
+        assert_eq!(config.theme, None);
+    }
+
+    #[test]
+    fn unknown_names_are_refused() {
+        let problems = Config::parse("[game]\nnmae = \"Albin\"\n[sound]\nvolume = 3\n").unwrap_err();
+        assert_eq!(problems.len(), 2);
+        assert!(problems.contains(&String::from("unknown setting 'nmae' in [game]")));
+        assert!(problems.contains(&String::from("unknown section [sound]")));
+    }
+
+    #[test]
+    fn bad_values_are_all_listed() {
+        let problems = Config::parse("[game]\nruleset = \"poker\"\nundo = -1\n\
+                                      [display]\nanimation_speed = \"fast\"\nodds = 1\n").unwrap_err();
+        assert_eq!(problems, vec![
+            String::from("[game] ruleset: 'poker' is not one of: yatzy, yahtzee, maxi-yatzy"),
+            String::from("[game] undo: can't be negative"),
+            String::from("[display] animation_speed: needs to be a number"),
+            String::from("[display] odds: needs to be true or false"),
+        ]);
+    }
+
+    #[test]
+    fn keys_bound_twice_are_refused() {
+        let problems = Config::parse("[keys]\npreset = \"vim\"\nundo = \"h\"\ntoggle = []\n").unwrap_err();
+        assert_eq!(problems, vec![
+            String::from("[keys] toggle needs at least one key"),
+            String::from("[keys] h is bound to both left and undo"),
+        ]);
+        let config = Config::parse("[keys]\nundo = \"-\"\n").unwrap();
+        assert_eq!(config.keys.describe(Action::Undo), Some(String::from("-")));
+    }
+}
@@ -0,0 +1,311 @@
+//////////////////////////////////////////////////////////////////////////
+//                                                                      //
+// Rusty Yacht - A yatzy game for the terminal               R U S T Y  //
+// Copyright (C) 2019  Albin Söderqvist <albin@fripost.org>  U       A  //
+//                                                           S       C  //
+// This game is free software: you can redistribute it       T       H  //
+// and/or modify it under the terms of GNU General Public    Y A C H T  //
+// License as published by the Free Software Foundation,                //
+// either version 3 of the License, or (at your option)                 //
+// any later version.                                                   //
+//                                                                      //
+// Rusty Yacht is distributed in the hope that it will be fun to play,  //
+// but WITHOUT ANY WARRANTY; without even the implied warranty of       //
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the         //
+// GNU General Public License for more details.                         //
+//                                                                      //
+// You should have received a copy of the GNU General Public License    //
+// along with the game. If not, see <https://www.gnu.org/licenses/>.    //
+//                                                                      //
+//////////////////////////////////////////////////////////////////////////
+
+//! Which key does what while picking dice and slots. The keys start from a
+//! preset and the config file can give any action keys of its own; Ctrl+c
+//! and Ctrl+z always keep their meaning.
+
+use std::fmt;
+
+use termion::event::Key;
+
+/// Something a player can do with a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Move the cursor to the die on the left, or the slot above.
+    Left,
+    /// Move the cursor to the die on the right, or the slot below.
+    Right,
+    /// Like `Right` for dice and `Left` for slots, as the arrow keys go.
+    Up,
+    /// Like `Left` for dice and `Right` for slots.
+    Down,
+    /// Keep or stop keeping the die under the cursor.
+    Toggle,
+    /// Keep or stop keeping die number n, counting from zero.
+    Die(usize),
+    /// Reroll the dice that are not kept.
+    Roll,
+    /// Score the dice in the slot under the cursor.
+    Score,
+    /// Strike out the slot under the cursor.
+    Strike,
+    Hint,
+    Undo,
+}
+
+/// Where a key is pressed. The same key can do different things on the
+/// two screens, but only one thing on each.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Screen {
+    Dice,
+    Slots,
+}
+
+/// The most dice any ruleset rolls.
+const MAX_DICE: usize = 6;
+
+impl Action {
+    /// Every action, in the order the config file lists them.
+    pub fn all() -> Vec<Action> {
+        let mut actions = vec![Action::Left, Action::Right, Action::Up, Action::Down, Action::Toggle];
+        actions.extend((0..MAX_DICE).map(Action::Die));
+        actions.extend(&[Action::Roll, Action::Score, Action::Strike, Action::Hint, Action::Undo]);
+        actions
+    }
+
+    /// The name of the action in the config file.
+    pub fn name(self) -> String {
+        match self {
+            Action::Left => String::from("left"),
+            Action::Right => String::from("right"),
+            Action::Up => String::from("up"),
+            Action::Down => String::from("down"),
+            Action::Toggle => String::from("toggle"),
+            Action::Die(n) => format!("die-{}", n + 1),
+            Action::Roll => String::from("roll"),
+            Action::Score => String::from("score"),
+            Action::Strike => String::from("strike"),
+            Action::Hint => String::from("hint"),
+            Action::Undo => String::from("undo"),
+        }
+    }
+
+    pub fn by_name(name: &str) -> Option<Action> {
+        Action::all().into_iter().find(|action| action.name() == name)
+    }
+
+    /// Whether the action can be taken on `screen`.
+    pub fn is_on(self, screen: Screen) -> bool {
+        match self {
+            Action::Left | Action::Right | Action::Up | Action::Down | Action::Hint => true,
+            Action::Toggle | Action::Die(_) | Action::Roll | Action::Undo => screen == Screen::Dice,
+            Action::Score | Action::Strike => screen == Screen::Slots,
+        }
+    }
+}
+
+/// The keys bound to each action.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keymap {
+    bindings: Vec<(Action, Vec<Key>)>,
+}
+
+impl Keymap {
+    /// The names of the presets, the default first.
+    pub const PRESETS: &'static [&'static str] = &["default", "vim", "numbers"];
+
+    /// The keys of the preset called `name`: default has the arrow keys,
+    /// vim adds h, j, k and l with ? for hints, and numbers adds 1 to 6 to
+    /// toggle a die without moving the cursor to it.
+    pub fn preset(name: &str) -> Option<Keymap> {
+        let mut keymap = Keymap {
+            bindings: vec![
+                (Action::Left, vec![Key::Left]),
+                (Action::Right, vec![Key::Right]),
+                (Action::Up, vec![Key::Up]),
+                (Action::Down, vec![Key::Down]),
+                (Action::Toggle, vec![Key::Char(' ')]),
+                (Action::Roll, vec![Key::Char('\n')]),
+                (Action::Score, vec![Key::Char('\n')]),
+                (Action::Strike, vec![Key::Char('-')]),
+                (Action::Hint, vec![Key::Char('h')]),
+                (Action::Undo, vec![Key::Char('u')]),
+            ],
+        };
+        match name {
+            "default" => (),
+            "vim" => {
+                keymap.bind(Action::Left, vec![Key::Left, Key::Char('h')]);
+                keymap.bind(Action::Right, vec![Key::Right, Key::Char('l')]);
+                keymap.bind(Action::Up, vec![Key::Up, Key::Char('k')]);
+                keymap.bind(Action::Down, vec![Key::Down, Key::Char('j')]);
+                keymap.bind(Action::Hint, vec![Key::Char('?')]);
+            },
+            "numbers" => for n in 0..MAX_DICE {
+                let digit = (b'1' + n as u8) as char;
+                keymap.bind(Action::Die(n), vec![Key::Char(digit)]);
+            },
+            _ => return None,
+        }
+        Some(keymap)
+    }
+
+    /// Makes `keys` the only keys for `action`.
+    pub fn bind(&mut self, action: Action, keys: Vec<Key>) {
+        self.bindings.retain(|&(bound, _)| bound != action);
+        if !keys.is_empty() {
+            self.bindings.push((action, keys));
+        }
+    }
+
+    /// What `key` does on `screen`, if anything.
+    pub fn action(&self, screen: Screen, key: Key) -> Option<Action> {
+        self.bindings.iter()
+            .find(|&&(action, ref keys)| action.is_on(screen) && keys.contains(&key))
+            .map(|&(action, _)| action)
+    }
+
+    /// The keys bound to `action`.
+    pub fn keys(&self, action: Action) -> &[Key] {
+        self.bindings.iter()
+            .find(|&&(bound, _)| bound == action)
+            .map_or(&[], |(_, keys)| keys)
+    }
+
+    /// The name of the first key bound to `action`, to tell the player.
+    pub fn describe(&self, action: Action) -> Option<String> {
+        self.keys(action).first().map(|&key| KeyName(key).to_string())
+    }
+
+    /// How to tell the player to move the cursor.
+    pub fn describe_moves(&self) -> String {
+        let moves = [Action::Left, Action::Down, Action::Up, Action::Right];
+        let arrows = [Key::Left, Key::Down, Key::Up, Key::Right];
+        if moves.iter().zip(&arrows).all(|(&action, key)| self.keys(action).contains(key)) {
+            return String::from("the arrow keys");
+        }
+        let names: Vec<String> = moves.iter().filter_map(|&action| self.describe(action)).collect();
+        names.join("/")
+    }
+
+    /// Every pair of actions on the same screen that share a key.
+    pub fn conflicts(&self) -> Vec<(Action, Action, Key)> {
+        let mut conflicts = Vec::new();
+        for (i, &(first, ref keys)) in self.bindings.iter().enumerate() {
+            for &(second, ref others) in &self.bindings[i + 1..] {
+                let shared = [Screen::Dice, Screen::Slots].iter()
+                    .any(|&screen| first.is_on(screen) && second.is_on(screen));
+                if let Some(&key) = keys.iter().find(|key| shared && others.contains(key)) {
+                    conflicts.push((first, second, key));
+                }
+            }
+        }
+        conflicts
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Keymap {
+        Keymap::preset("default").unwrap()
+    }
+}
+
+/// Reads a key as the config file names it: a single character, or one of
+/// Enter, Space, Tab, Backspace, Delete, Insert, Home, End, PageUp,
+/// PageDown and the arrow keys Left, Right, Up and Down.
+pub fn parse_key(name: &str) -> Option<Key> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return if c.is_control() { None } else { Some(Key::Char(c)) };
+    }
+    let key = match &*name.to_lowercase() {
+        "enter" => Key::Char('\n'),
+        "space" => Key::Char(' '),
+        "tab" => Key::Char('\t'),
+        "backspace" => Key::Backspace,
+        "delete" => Key::Delete,
+        "insert" => Key::Insert,
+        "home" => Key::Home,
+        "end" => Key::End,
+        "pageup" => Key::PageUp,
+        "pagedown" => Key::PageDown,
+        "left" => Key::Left,
+        "right" => Key::Right,
+        "up" => Key::Up,
+        "down" => Key::Down,
+        _ => return None,
+    };
+    Some(key)
+}
+
+/// Shows a key the way `parse_key` reads it.
+pub struct KeyName(pub Key);
+
+impl fmt::Display for KeyName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Key::Char('\n') => write!(f, "Enter"),
+            Key::Char(' ') => write!(f, "Space"),
+            Key::Char('\t') => write!(f, "Tab"),
+            Key::Char(c) => write!(f, "{}", c),
+            Key::Backspace => write!(f, "Backspace"),
+            Key::Delete => write!(f, "Delete"),
+            Key::Insert => write!(f, "Insert"),
+            Key::Home => write!(f, "Home"),
+            Key::End => write!(f, "End"),
+            Key::PageUp => write!(f, "PageUp"),
+            Key::PageDown => write!(f, "PageDown"),
+            Key::Left => write!(f, "Left"),
+            Key::Right => write!(f, "Right"),
+            Key::Up => write!(f, "Up"),
+            Key::Down => write!(f, "Down"),
+            key => write!(f, "{:?}", key),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_names_read_back() {
+        for name in &["a", "?", "Enter", "Space", "Tab", "Backspace", "PageDown", "Left"] {
+            let key = parse_key(name).unwrap();
+            assert_eq!(KeyName(key).to_string(), *name);
+        }
+        assert_eq!(parse_key("ENTER"), Some(Key::Char('\n')));
+        assert_eq!(parse_key("\t"), None);
+        assert_eq!(parse_key("F1"), None);
+        assert_eq!(parse_key(""), None);
+    }
+
+    #[test]
+    fn presets_add_to_the_default_keys() {
+        assert_eq!(Keymap::preset("shortcuts"), None);
+        let vim = Keymap::preset("vim").unwrap();
+        assert_eq!(vim.action(Screen::Dice, Key::Char('h')), Some(Action::Left));
+        assert_eq!(vim.action(Screen::Slots, Key::Left), Some(Action::Left));
+        assert_eq!(vim.describe(Action::Hint), Some(String::from("?")));
+        let numbers = Keymap::preset("numbers").unwrap();
+        assert_eq!(numbers.action(Screen::Dice, Key::Char('3')), Some(Action::Die(2)));
+        assert_eq!(numbers.action(Screen::Slots, Key::Char('3')), None);
+        for name in Keymap::PRESETS {
+            assert!(Keymap::preset(name).unwrap().conflicts().is_empty());
+        }
+    }
+
+    #[test]
+    fn one_key_for_each_screen() {
+        let mut keymap = Keymap::default();
+        assert_eq!(keymap.action(Screen::Dice, Key::Char('\n')), Some(Action::Roll));
+        assert_eq!(keymap.action(Screen::Slots, Key::Char('\n')), Some(Action::Score));
+        keymap.bind(Action::Undo, vec![Key::Char('x')]);
+        keymap.bind(Action::Strike, vec![Key::Char('x')]);
+        assert!(keymap.conflicts().is_empty());
+        keymap.bind(Action::Toggle, vec![Key::Char('x'), Key::Char(' ')]);
+        assert_eq!(keymap.conflicts(), vec![(Action::Undo, Action::Toggle, Key::Char('x'))]);
+        assert_eq!(keymap.keys(Action::Toggle), &[Key::Char('x'), Key::Char(' ')]);
+        keymap.bind(Action::Hint, Vec::new());
+        assert_eq!(keymap.describe(Action::Hint), None);
+    }
+}
@@ -20,1371 +20,2019 @@
 //////////////////////////////////////////////////////////////////////////
 
 extern crate chrono;
+extern crate libc;
 extern crate rand;
 extern crate termion;
+extern crate toml;
 
-use rand::Rng;
-use std::io::{Write, stdout, stdin, BufRead, BufReader, ErrorKind};
+use rand::{Rng, RngCore, SeedableRng};
+use rand::rngs::StdRng;
+use std::io::{self, Write, BufRead, BufReader, ErrorKind};
 use std::fs::*;
 use std::fs::create_dir_all;
 use std::path::PathBuf;
+use std::collections::VecDeque;
+use std::fmt;
 use chrono::prelude::*;
-use termion::clear;
-use termion::event::Key;
-use termion::raw::IntoRawMode;
-use termion::style;
-use termion::input::TermRead;
-
-pub enum DiceSelectStatus {
-    Complete,
-    Exit,
-    Incomplete,
-}
+use bot::Strategy;
 
-pub enum SlotSelectStatus {
-    AlreadySelected,
-    Exit,
-    Invalid,
-    Complete,
-    Incomplete,
-}
+pub mod analysis;
+pub mod bot;
+pub mod config;
+pub mod keys;
+pub mod odds;
+pub mod save;
+pub mod solver;
+pub mod terminal;
+pub mod theme;
+pub mod ui;
+
+pub type Validator = fn(&[usize]) -> Option<usize>;
 
 pub struct ScoreValidator;
 
 impl ScoreValidator {
-    pub fn new() -> [fn(&[usize; 5]) -> Option<usize>; 15] {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> [Validator; 15] {
+        [ScoreValidator::ones, ScoreValidator::twos, ScoreValidator::threes,
+         ScoreValidator::fours, ScoreValidator::fives, ScoreValidator::sixes,
+         ScoreValidator::one_pair, ScoreValidator::two_pairs,
+         ScoreValidator::three_kind, ScoreValidator::four_kind,
+         ScoreValidator::small_str, ScoreValidator::large_str,
+         ScoreValidator::full_house, ScoreValidator::chance,
+         ScoreValidator::yatzy]
+    }
 
-        fn ones(current: &[usize; 5]) -> Option<usize> {
-            let mut value: usize = 0;
-            for i in current {
-                if i == &1 {
-                    value += i;
-                }
-            }
-            if value > 0 {
-                return Some(value);
-            }
-            None
-        }
- 
-       fn twos(current: &[usize; 5]) -> Option<usize> {
-            let mut value: usize = 0;
-            for i in current {
-                if i == &2 {
-                    value += i;
-                }
+    pub fn ones(current: &[usize]) -> Option<usize> {
+        let mut value: usize = 0;
+        for i in current {
+            if i == &1 {
+                value += i;
             }
+        }
+        if value > 0 {
+            return Some(value);
+        }
+        None
+    }
 
-            if value > 1 {
-                return Some(value);
+    pub fn twos(current: &[usize]) -> Option<usize> {
+        let mut value: usize = 0;
+        for i in current {
+            if i == &2 {
+                value += i;
             }
-            None
         }
 
-        fn threes(current: &[usize; 5]) -> Option<usize> {
-            let mut value: usize = 0;
-            for i in current {
-                if i == &3 {
-                    value += i;
-                }
-            }
+        if value > 1 {
+            return Some(value);
+        }
+        None
+    }
 
-            if value > 2 {
-                return Some(value);
+    pub fn threes(current: &[usize]) -> Option<usize> {
+        let mut value: usize = 0;
+        for i in current {
+            if i == &3 {
+                value += i;
             }
-            None
         }
 
-        fn fours(current: &[usize; 5]) -> Option<usize> {
-            let mut value: usize = 0;
-            for i in current {
-                if i == &4 {
-                    value += i;
-                }
-            }
+        if value > 2 {
+            return Some(value);
+        }
+        None
+    }
 
-            if value > 3 {
-                return Some(value);
+    pub fn fours(current: &[usize]) -> Option<usize> {
+        let mut value: usize = 0;
+        for i in current {
+            if i == &4 {
+                value += i;
             }
-            None
         }
 
-        fn fives(current: &[usize; 5]) -> Option<usize> {
-            let mut value: usize = 0;
-            for i in current {
-                if i == &5 {
-                    value += i;
-                }
-            }
-            if value > 4 {
-                return Some(value);
-            }
-            None
+        if value > 3 {
+            return Some(value);
         }
+        None
+    }
 
-        fn sixes(current: &[usize; 5]) -> Option<usize> {
-            let mut value: usize = 0;
-            for i in current {
-                if i == &6 {
-                    value += i;
-                }
+    pub fn fives(current: &[usize]) -> Option<usize> {
+        let mut value: usize = 0;
+        for i in current {
+            if i == &5 {
+                value += i;
             }
-            if value > 5 {
-                return Some(value);
-            }
-            None
         }
+        if value > 4 {
+            return Some(value);
+        }
+        None
+    }
 
-        fn one_pair(current: &[usize; 5]) -> Option<usize> {
-            let mut dice_str: [usize; 5] = current.clone();
-            dice_str.sort();
-            let mut value: usize = 0;
-            if dice_str[0] == dice_str[1] {
-                value += dice_str[0] + dice_str[1];
-            }
-            if dice_str[1] == dice_str[2] {
-                if dice_str[1] + dice_str[2] > value {
-                    value = dice_str[1] + dice_str[2]
-                }
-            }
-            if dice_str[2] == dice_str[3] {
-                if dice_str[2] + dice_str[3] > value {
-                    value = dice_str[2] + dice_str[3]
-                }
-            }
-            if dice_str[3] == dice_str[4] {
-                if dice_str[3] + dice_str[4] > value {
-                    value = dice_str[3] + dice_str[4]
-                }
+    pub fn sixes(current: &[usize]) -> Option<usize> {
+        let mut value: usize = 0;
+        for i in current {
+            if i == &6 {
+                value += i;
             }
+        }
+        if value > 5 {
+            return Some(value);
+        }
+        None
+    }
 
-            if value > 1 {
-                return Some(value);
-            }
+    pub fn one_pair(current: &[usize]) -> Option<usize> {
+        let pairs = ScoreValidator::faces_with(current, 2);
+        pairs.first().map(|face| face * 2)
+    }
 
-            None
+    pub fn two_pairs(current: &[usize]) -> Option<usize> {
+        let pairs = ScoreValidator::faces_with(current, 2);
+        if pairs.len() < 2 {
+            return None;
         }
+        Some(pairs[0] * 2 + pairs[1] * 2)
+    }
 
-        fn two_pairs(current: &[usize; 5]) -> Option<usize> {
-            let mut dice_str: [usize; 5] = current.clone();
-            dice_str.sort();
-            let mut value_fst: usize = 0;
-            let mut value_snd: usize = 0;
-            if dice_str[0] == dice_str[1] {
-                value_fst = dice_str[0] + dice_str[1];
-            } else if dice_str[1] == dice_str[2] {
-                value_fst = dice_str[1] + dice_str[2];
-            }
+    pub fn three_pairs(current: &[usize]) -> Option<usize> {
+        let pairs = ScoreValidator::faces_with(current, 2);
+        if pairs.len() < 3 {
+            return None;
+        }
+        Some(pairs[0] * 2 + pairs[1] * 2 + pairs[2] * 2)
+    }
 
-            if dice_str[2] == dice_str[3] {
-                value_snd = dice_str[2] + dice_str[3];
-            } else if dice_str[3] == dice_str[4] {
-                value_snd = dice_str[3] + dice_str[4];
-            }
+    pub fn three_kind(current: &[usize]) -> Option<usize> {
+        let triples = ScoreValidator::faces_with(current, 3);
+        triples.first().map(|face| face * 3)
+    }
 
-            if value_fst > 1 && value_snd > 1 &&
-                value_fst != value_snd {
-                    return Some(value_fst + value_snd);
-                }
-            None
+    pub fn four_kind(current: &[usize]) -> Option<usize> {
+        let quads = ScoreValidator::faces_with(current, 4);
+        quads.first().map(|face| face * 4)
+    }
+
+    pub fn five_kind(current: &[usize]) -> Option<usize> {
+        let fives = ScoreValidator::faces_with(current, 5);
+        fives.first().map(|face| face * 5)
+    }
+
+    pub fn small_str(current: &[usize]) -> Option<usize> {
+        if (1..6).all(|face| current.contains(&face)) {
+            return Some(15);
         }
+        None
+    }
 
-        fn three_kind(current: &[usize; 5]) -> Option<usize> {
-            let mut dice_str: [usize; 5] = current.clone();
-            dice_str.sort();
-            let mut value: usize = 0;
-            if dice_str[0] == dice_str[1] && dice_str[1] == dice_str[2] {
-                for (i, die) in dice_str.iter().enumerate() {
-                    if i < 3 {
-                        value += die;
-                    }
-                }
-                return Some(value);
+    pub fn large_str(current: &[usize]) -> Option<usize> {
+        if (2..7).all(|face| current.contains(&face)) {
+            return Some(20);
+        }
+        None
+    }
 
-            } else if dice_str[1] == dice_str[2] && dice_str[2] == dice_str[3] {
-                for (i, die) in dice_str.iter().enumerate() {
-                    if i > 0 && i < 4 {
-                        value += die;
-                    }
-                }
-                return Some(value);
+    pub fn full_str(current: &[usize]) -> Option<usize> {
+        if (1..7).all(|face| current.contains(&face)) {
+            return Some(21);
+        }
+        None
+    }
 
-            } else if dice_str[2] == dice_str[3] && dice_str[3] == dice_str[4] {
-                for (i, die) in dice_str.iter().enumerate() {
-                    if i > 1 {
-                        value += die;
-                    }
-                }
-                return Some(value);
+    /// Three of one kind and a pair of another. Five of a kind counts too,
+    /// as it always has in this game.
+    pub fn full_house(current: &[usize]) -> Option<usize> {
+        ScoreValidator::combined(current, 3, 2).or_else(|| ScoreValidator::five_kind(current))
+    }
+
+    /// Three of one kind and three of another.
+    pub fn castle(current: &[usize]) -> Option<usize> {
+        ScoreValidator::combined(current, 3, 3)
+    }
+
+    /// Four of one kind and a pair of another.
+    pub fn tower(current: &[usize]) -> Option<usize> {
+        ScoreValidator::combined(current, 4, 2)
+    }
+
+    pub fn chance(current: &[usize]) -> Option<usize> {
+        let value = current.iter().sum();
+        Some(value)
+    }
+
+    pub fn yatzy(current: &[usize]) -> Option<usize> {
+        for item in current.iter() {
+            if item != &current[0] {
+                return None;
             }
-            None
-        }
-
-        fn four_kind(current: &[usize; 5]) -> Option<usize> {
-            let mut dice_str: [usize; 5] = current.clone();
-            dice_str.sort();
-            let mut value: usize = 0;
-            if dice_str[0] == dice_str[1] &&
-                dice_str[1] == dice_str[2] &&
-                dice_str[2] == dice_str[3] {
-                    for (i, die) in dice_str.iter().enumerate() {
-                        if i < 4 {
-                            value += die;
-                        }
-                    }
-                    return Some(value);
-                } else if dice_str[1] == dice_str[2] &&
-                dice_str[2] == dice_str[3] &&
-                dice_str[3] == dice_str[4] {
-                    for (i, die) in dice_str.iter().enumerate() {
-                        if i > 0 {
-                            value += die;
-                        }
-                    }
-                    return Some(value);
-                }
-            None
         }
+        Some(50)
+    }
+
+    pub fn maxi_yatzy(current: &[usize]) -> Option<usize> {
+        ScoreValidator::yatzy(current).map(|_| 100)
+    }
+
+    pub fn three_kind_sum(current: &[usize]) -> Option<usize> {
+        ScoreValidator::three_kind(current).map(|_| current.iter().sum())
+    }
+
+    pub fn four_kind_sum(current: &[usize]) -> Option<usize> {
+        ScoreValidator::four_kind(current).map(|_| current.iter().sum())
+    }
 
-        fn small_str(current: &[usize; 5]) -> Option<usize> {
-            let mut dice_str: [usize; 5] = current.clone();
-            dice_str.sort();
-            if dice_str == [1, 2, 3, 4, 5] {
-                return Some(15);
+    /// Unlike Full House in yatzy, a Yahtzee only counts as a Joker.
+    pub fn full_house_fixed(current: &[usize]) -> Option<usize> {
+        ScoreValidator::combined(current, 3, 2).map(|_| 25)
+    }
+
+    pub fn small_str_any(current: &[usize]) -> Option<usize> {
+        for lowest in 1..4 {
+            if (lowest..lowest + 4).all(|face| current.contains(&face)) {
+                return Some(30);
             }
-            None
         }
+        None
+    }
 
-        fn large_str(current: &[usize; 5]) -> Option<usize> {
-            let mut dice_str: [usize; 5] = current.clone();
-            dice_str.sort();
-            if dice_str == [2, 3, 4, 5, 6] {
-                    return Some(20);
+    pub fn large_str_any(current: &[usize]) -> Option<usize> {
+        for lowest in 1..3 {
+            if (lowest..lowest + 5).all(|face| current.contains(&face)) {
+                return Some(40);
             }
-            None
-        }
-
-        fn full_house(current: &[usize; 5]) -> Option<usize> {
-            let mut dice_str: [usize; 5] = current.clone();
-            dice_str.sort();
-            if ((dice_str[0] == dice_str[1] && dice_str[1] == dice_str[2]) &&
-                (dice_str[3] == dice_str[4])) ||
-                ((dice_str[0] == dice_str[1]) &&
-                 (dice_str[2] == dice_str[3] && dice_str[3] == dice_str[4])) &&
-                ((dice_str[0] == dice_str[1]
-                  && dice_str[1] == dice_str[2]
-                  && dice_str[2] == dice_str[3]) == false) {
-                    let value = current.iter().fold(0,|a, &b| a + b);
-                    return Some(value);
-                }
-            None
         }
+        None
+    }
 
-        fn chance(current: &[usize; 5]) -> Option<usize> {
-            let value = current.iter().fold(0,|a, &b| a + b);
-            Some(value)
-        }
+    /// The faces shown by at least `count` dice, highest first.
+    fn faces_with(current: &[usize], count: usize) -> Vec<usize> {
+        (1..7).rev()
+            .filter(|face| current.iter().filter(|&die| die == face).count() >= count)
+            .collect()
+    }
 
-        fn yatzy(current: &[usize; 5]) -> Option<usize> {
-            for (i, &_item) in current.iter().enumerate() {
-                if current[i] != current[0] {
-                    return None;
+    /// The best score from `first` dice of one face and `second` dice of
+    /// another, or `None` if there are no such dice.
+    fn combined(current: &[usize], first: usize, second: usize) -> Option<usize> {
+        let mut best = None;
+        for &a in &ScoreValidator::faces_with(current, first) {
+            for &b in &ScoreValidator::faces_with(current, second) {
+                if a != b {
+                    best = best.max(Some(a * first + b * second));
                 }
             }
-            Some(50)
         }
-
-        [ones, twos, threes, fours, fives, sixes, one_pair,
-         two_pairs, three_kind, four_kind, small_str,
-         large_str, full_house, chance, yatzy]
+        best
     }
 }
 
-pub struct ScoreCategory {
-    name: &'static str,
-    value: String,
-    selected: bool,
-    highlighted: bool,
+/// The score slots a player fills in, top to bottom.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Category {
+    Ones,
+    Twos,
+    Threes,
+    Fours,
+    Fives,
+    Sixes,
+    OnePair,
+    TwoPairs,
+    ThreeOfAKind,
+    FourOfAKind,
+    SmallStraight,
+    LargeStraight,
+    FullHouse,
+    Chance,
+    Yatzy,
+    ThreePairs,
+    FiveOfAKind,
+    FullStraight,
+    Castle,
+    Tower,
 }
 
-pub struct Score([ScoreCategory; 18]);
+impl Category {
+    /// Every category of every ruleset.
+    pub const ALL: [Category; 20] = [
+        Category::Ones, Category::Twos, Category::Threes, Category::Fours,
+        Category::Fives, Category::Sixes, Category::OnePair, Category::TwoPairs,
+        Category::ThreeOfAKind, Category::FourOfAKind, Category::SmallStraight,
+        Category::LargeStraight, Category::FullHouse, Category::Chance,
+        Category::Yatzy, Category::ThreePairs, Category::FiveOfAKind,
+        Category::FullStraight, Category::Castle, Category::Tower,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Category::Ones => "Ones",
+            Category::Twos => "Twos",
+            Category::Threes => "Threes",
+            Category::Fours => "Fours",
+            Category::Fives => "Fives",
+            Category::Sixes => "Sixes",
+            Category::OnePair => "One Pair",
+            Category::TwoPairs => "Two Pairs",
+            Category::ThreeOfAKind => "Three of a Kind",
+            Category::FourOfAKind => "Four of a Kind",
+            Category::SmallStraight => "Small Straight",
+            Category::LargeStraight => "Large Straight",
+            Category::FullHouse => "Full House",
+            Category::Chance => "Chance",
+            Category::Yatzy => "Yatzy",
+            Category::ThreePairs => "Three Pairs",
+            Category::FiveOfAKind => "Five of a Kind",
+            Category::FullStraight => "Full Straight",
+            Category::Castle => "Castle",
+            Category::Tower => "Tower",
+        }
+    }
+
+    /// Returns true for Ones through Sixes, which count towards the bonus.
+    pub fn is_upper(self) -> bool {
+        (self as usize) < 6
+    }
 
-impl<'a> IntoIterator for &'a Score {
-    type Item = &'a ScoreCategory;
-    type IntoIter = std::slice::Iter<'a, ScoreCategory>;
+    /// The upper section category counting dice showing `face`.
+    pub fn upper(face: usize) -> Option<Category> {
+        match face {
+            1..=6 => Some(Category::ALL[face - 1]),
+            _ => None,
+        }
+    }
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.0.into_iter()
+    /// The face counted by an upper section category.
+    pub fn face(self) -> Option<usize> {
+        if self.is_upper() { Some(self as usize + 1) } else { None }
     }
 }
 
-impl std::ops::Deref for Score {
-    type Target = [ScoreCategory];
+/// The contents of a single score slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlotState {
+    Empty,
+    Scored(u16),
+    Struck,
+}
 
-    fn deref(&self) -> &[ScoreCategory] {
-        &self.0
+impl SlotState {
+    pub fn is_empty(self) -> bool {
+        self == SlotState::Empty
     }
-}
 
-impl std::ops::DerefMut for Score {
-    fn deref_mut(&mut self) -> &mut [ScoreCategory] {
-        &mut self.0
+    /// The points in the slot; empty and struck slots count as zero.
+    pub fn points(self) -> u16 {
+        match self {
+            SlotState::Scored(points) => points,
+            _ => 0,
+        }
     }
 }
 
-impl Score {
-    pub fn new() -> Score {
-        // initialize score values
-        let score_ones = ScoreCategory {
-            name: "Ones", value: "  ".to_string(), selected: false, highlighted: false
-        };
-        let score_twos = ScoreCategory {
-            name: "Twos", value: "  ".to_string(), selected: false, highlighted: false
-        };
-        let score_threes = ScoreCategory {
-            name: "Threes", value: "  ".to_string(), selected: false, highlighted: false
-        };
-        let score_fours = ScoreCategory {
-            name: "Fours", value: "  ".to_string(), selected: false, highlighted: false
-        };
-        let score_fives = ScoreCategory {
-            name: "Fives", value: "  ".to_string(), selected: false, highlighted: false
-        };
-        let score_sixes = ScoreCategory {
-            name: "Sixes", value: "  ".to_string(), selected: false, highlighted: false
-        };
-        let score_bonus = ScoreCategory {
-            name: "Bonus", value: "  ".to_string(), selected: false, highlighted: false
-        };
-        let score_one_pair = ScoreCategory {
-            name: "One Pair", value: "  ".to_string(), selected: false, highlighted: false
-        };
-        let score_two_pairs = ScoreCategory {
-            name: "Two Pairs", value: "  ".to_string(), selected: false, highlighted: false
-        };
-        let score_three_kind = ScoreCategory {
-            name: "Three of a Kind", value: "  ".to_string(), selected: false, highlighted: false
-        };
-        let score_four_kind = ScoreCategory {
-            name: "Four of a Kind", value: "  ".to_string(), selected: false, highlighted: false
-        };
-        let score_small_str = ScoreCategory {
-            name: "Small Straight", value: "  ".to_string(), selected: false, highlighted: false
-        };
-        let score_large_str = ScoreCategory {
-            name: "Large Straight", value: "  ".to_string(), selected: false, highlighted: false
-        };
-        let score_full_house = ScoreCategory {
-            name: "Full House", value: "  ".to_string(), selected: false, highlighted: false
-        };
-        let score_chance = ScoreCategory {
-            name: "Chance", value: "  ".to_string(), selected: false, highlighted: false
-        };
-        let score_yatzy = ScoreCategory {
-            name: "Yatzy", value: "  ".to_string(), selected: false, highlighted: false
-        };
-        let score_subtotal = ScoreCategory {
-            name: "Sum", value: "   ".to_string(), selected: false, highlighted: false
-        };
-        let score_total = ScoreCategory {
-            name: "Total", value: "   ".to_string(), selected: false, highlighted: false
-        };
+/// How the score sheet is laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    /// A single Sum and Bonus between the sections and one Total.
+    Scandinavian,
+    /// Separate upper and lower totals, a Yahtzee Bonus row and a Grand
+    /// Total, as on American score pads.
+    American,
+}
 
-        // Return the score values as a Score struct
-        Score ([score_ones, score_twos, score_threes, score_fours,
-                 score_fives, score_sixes, score_one_pair, score_two_pairs,
-                 score_three_kind, score_four_kind, score_small_str,
-                 score_large_str, score_full_house, score_chance,
-                 score_yatzy, score_bonus, score_subtotal, score_total])
+/// The rules of a yatzy variant: which slots the score sheet has, how the
+/// dice are scored in them and how many dice and rerolls a player gets.
+pub trait Ruleset {
+    /// The name shown to players.
+    fn name(&self) -> &'static str;
+
+    /// The name used to pick the ruleset, e.g. on the command line.
+    fn id(&self) -> String {
+        self.name().to_lowercase().replace(' ', "-")
     }
 
-    pub fn print(&mut self) {
+    /// The slots of the score sheet, top to bottom.
+    fn categories(&self) -> &'static [Category];
 
-        let mut subtotal: u8 = 0;
-        let mut total: u16 = 0;
+    fn validator(&self, category: Category) -> Validator;
 
-        for elm in self[0..6].iter() {
-            if elm.value != "  " && elm.value != "   " && elm.value != " –" {
-                subtotal += elm.value.trim().parse::<u8>().unwrap();
-            }
-        }
-        if subtotal > 0 {
-            self[16].value = format!("{:>3}", subtotal);
-        }
-        
-        if total > 0 {
-            self[17].value = format!("{:>3}", total);
-        }
-
-        if subtotal > 62 {
-            self[15].value = format!("50");
-        } else if
-            self[0].value != "  " &&
-            self[1].value != "  " &&
-            self[2].value != "  " &&
-            self[3].value != "  " &&
-            self[4].value != "  " &&
-            self[5].value != "  "
-        {
-            self[15].value = format!(" –");
-        }
-
-        for elm in self.iter() {
-            if elm.value != "  " &&
-                elm.value != "   " &&
-                elm.value != " –" &&
-                elm.name != "Sum" {
-                    total += elm.value.trim().parse::<u16>().unwrap();
-                }
-        }
+    /// The label of `category` on the score sheet.
+    fn label(&self, category: Category) -> &'static str {
+        category.name()
+    }
 
-        if total > 0 {
-            self[17].value = format!("{:>3}", total);
-        }
-
-        println!("╔═══════════════════════════════════════════════╗");
-        println!("║ RUSTY YACHT                                   ║");
-        println!("╠═══════════════════════════╦═══════════════════╣");
-        println!("║                       Max ║             Score ║");
-        println!("╟───────────────────────────╫───────────────────╢");
-        if self[0].highlighted == true {
-            println!("║{} Ones                    5 {}║{}                {} {}║",
-                     style::Invert, style::Reset, style::Invert,
-                     self[0].value, style::Reset);
-        } else { println!("║ Ones                    5 ║                {} ║",
-                          self[0].value); }
-        if self[1].highlighted == true {
-            println!("║{} Twos                   10 {}║{}                {} {}║",
-                     style::Invert, style::Reset, style::Invert, self[1].value, style::Reset);
-        } else {
-            println!("║ Twos                   10 ║                {} ║",
-                     self[1].value); }
-        if self[2].highlighted == true {
-            println!("║{} Threes                 15 {}║{}                {} {}║",
-                     style::Invert, style::Reset, style::Invert, self[2].value, style::Reset);
-        } else {
-            println!("║ Threes                 15 ║                {} ║",
-                     self[2].value); }
-        if self[3].highlighted == true {
-            println!("║{} Fours                  20 {}║{}                {} {}║",
-                     style::Invert, style::Reset, style::Invert, self[3].value, style::Reset);
-        } else {println!("║ Fours                  20 ║                {} ║",
-                         self[3].value); }
-        if self[4].highlighted == true {
-            println!("║{} Fives                  25 {}║{}                {} {}║",
-                     style::Invert, style::Reset, style::Invert, self[4].value, style::Reset);
-        } else {
-            println!("║ Fives                  25 ║                {} ║",
-                     self[4].value); }
-        if self[5].highlighted == true {
-            println!("║{} Sixes                  30 {}║{}                {} {}║",
-                     style::Invert, style::Reset, style::Invert, self[5].value, style::Reset);
-        } else { println!("║ Sixes                  30 ║                {} ║",
-                          self[5].value); }
-        println!("╟───────────────────────────╫───────────────────╢");
-        // if self[16].highlighted == true {
-        //     println!("║{} Sum                105 {}║{}                    {} {}║",
-        //              style::Invert, style::Reset, style::Invert, self[16].value, style::Reset); } else {
-        println!("║ Sum                   105 ║               {} ║", self[16].value);
-        println!("║ Bonus                  50 ║                {} ║", self[15].value);
-        //println!("╟───────────────────────────╫───────────────────╢");
-        if self[6].highlighted == true {
-            println!("║{} One Pair               12 {}║{}                {} {}║",
-                     style::Invert, style::Reset, style::Invert, self[6].value, style::Reset);
-        } else { println!("║ One Pair               12 ║                {} ║",
-                          self[6].value); }
-        if self[7].highlighted == true {
-            println!("║{} Two Pairs              22 {}║{}                {} {}║",
-                     style::Invert, style::Reset, style::Invert, self[7].value, style::Reset);
-        } else {
-            println!("║ Two Pairs              22 ║                {} ║",
-                     self[7].value); }
-        if self[8].highlighted == true {
-            println!("║{} Three of a Kind        18 {}║{}                {} {}║",
-                     style::Invert, style::Reset, style::Invert, self[8].value, style::Reset);
-        } else {
-            println!("║ Three of a Kind        18 ║                {} ║",
-                     self[8].value);
-        }
-        if self[9].highlighted == true {
-            println!("║{} Four of a Kind         24 {}║{}                {} {}║",
-                     style::Invert, style::Reset, style::Invert, self[9].value, style::Reset);
-        } else {
-            println!("║ Four of a Kind         24 ║                {} ║",
-                     self[9].value);
-        }
-        if self[10].highlighted == true {
-            println!("║{} Small Straight         15 {}║{}                {} {}║",
-                     style::Invert, style::Reset, style::Invert, self[10].value, style::Reset);
-        } else {
-            println!("║ Small Straight         15 ║                {} ║",
-                     self[10].value);
-        }
-        if self[11].highlighted == true {
-            println!("║{} Large Straight         20 {}║{}                {} {}║",
-                     style::Invert, style::Reset, style::Invert, self[11].value, style::Reset);
-        } else {
-            println!("║ Large Straight         20 ║                {} ║",
-                     self[11].value);
-        }
-        if self[12].highlighted == true {
-            println!("║{} Full House             28 {}║{}                {} {}║",
-                     style::Invert, style::Reset, style::Invert, self[12].value, style::Reset);
-        } else {
-            println!("║ Full House             28 ║                {} ║",
-                     self[12].value);
-        }
-        if self[13].highlighted == true {
-            println!("║{} Chance                 30 {}║{}                {} {}║",
-                     style::Invert, style::Reset, style::Invert, self[13].value, style::Reset);
-        } else {
-            println!("║ Chance                 30 ║                {} ║",
-                     self[13].value);
-        }
-        if self[14].highlighted == true {
-            println!("║{} Yatzy                  50 {}║{}                {} {}║",
-                     style::Invert, style::Reset, style::Invert, self[14].value, style::Reset);
-        } else {
-            println!("║ Yatzy                  50 ║                {} ║",
-                     self[14].value);
-        }
-        println!("╟───────────────────────────╫───────────────────╢");
-        println!("║ Total                 374 ║               {:>3} ║", self[17].value);
-        println!("╚═══════════════════════════╩═══════════════════╝");
-
-        self[17].value = format!("   ");
-
-    }
-
-    pub fn place_points(&mut self, validators: [fn(&[usize; 5]) -> Option<usize>; 15],
-                        dice: &Dice) {
-        let mut i: usize = 0;
-        loop {
-            self[i].highlighted = true;
-            self.print();
-            dice.print();
-
-            match self.select_slot(&dice, validators, &mut i) {
-                SlotSelectStatus::Exit => std::process::exit(0),
-                SlotSelectStatus::AlreadySelected => { println!("{}", clear::All);
-                                                       println!("  Sorry, you can't use this slot again.");
-                                                       println!("  Press Enter to continue.");
-                                                       self.print();
-                                                       dice.print();
-                                                       let stdin = stdin();
-                                                       let mut stdout = stdout().into_raw_mode().unwrap();
-                                                       write!(stdout, "{}", termion::cursor::Hide).unwrap();
-                                                       for c in stdin.keys() {
-                                                           match c.unwrap() {
-                                                               Key::Ctrl(c) => if c == 'c' {
-                                                                   stdout.suspend_raw_mode().unwrap();
-                                                                   std::process::exit(0);
-                                                               },
-                                                               Key::Char('\n') => {
-                                                                   println!("{}", clear::All);
-                                                                   break;
-                                                               },
-                                                               Key::Up => {
-                                                                   if i != 0 {
-                                                                       i -= 1;
-                                                                       self[i+1].highlighted = false;
-                                                                   } else {
-                                                                       i = 14;
-                                                                       self[0].highlighted = false;
-                                                                   }
-                                                                   self[i].highlighted = true;
-                                                                   println!("{}", clear::All);
-                                                                   break;
-                                                               },
-                                                               Key::Down => {
-                                                                   if i != 14 {
-                                                                       i += 1;
-                                                                       self[i-1].highlighted = false;
-                                                                   } else {
-                                                                       i = 0;
-                                                                       self[14].highlighted = false;
-                                                                   }
-                                                                   self[i].highlighted = true;
-                                                                   println!("{}", clear::All);
-                                                                   break;
-                                                               },
-                                                               _ => { println!("{}", clear::All);
-                                                                      continue;
-                                                               }
-                                                           }
-                                                       }
-                                                       stdout.flush().unwrap();
-                },
-
-                SlotSelectStatus::Complete  => { println!("{}", clear::All);
-                                                 println!("  Selection complete. Press Enter to continue.");
-                                                 self.print();
-                                                 dice.print();
-                                                 let stdin = stdin();
-                                                 let mut stdout = stdout().into_raw_mode().unwrap();
-                                                 write!(stdout, "{}", termion::cursor::Hide).unwrap();
-                                                 for c in stdin.keys() {
-                                                     match c.unwrap() {
-                                                         Key::Ctrl(c) => if c == 'c' {
-                                                             stdout.suspend_raw_mode().unwrap();
-                                                             std::process::exit(0);
-                                                         },
-                                                         Key::Char('\n') => break,
-                                                         _ => continue,
-                                                     }
-                                                 }
-                                                 stdout.flush().unwrap();
-                                                 break;
-                },
-                SlotSelectStatus::Incomplete => {
-                    println!("{}", clear::All);
-                },
-                SlotSelectStatus::Invalid => {
-                    println!("{}", clear::All);
-                    println!("  Invalid selection. Press - to strike it out");
-                    println!("  or an arrow key to cancel.");
-                },
-            }
-        }
-        self[i].highlighted = false;
-        println!("{}", clear::All);
+    /// Upper section sum needed for the bonus.
+    fn bonus_threshold(&self) -> u16 {
+        63
     }
-    pub fn is_final(&self) -> bool {
-        for item in self.iter() {
-            if item.name == "Bonus" {
-                continue;
-            }
-            if item.value == "  " {
-                return false;
-            }
-        }
+
+    /// Points awarded for reaching the bonus threshold.
+    fn bonus(&self) -> u16 {
+        50
+    }
+
+    fn layout(&self) -> Layout {
+        Layout::Scandinavian
+    }
+
+    fn dice(&self) -> usize {
+        5
+    }
+
+    /// Number of rerolls after the first roll of each turn.
+    fn rerolls(&self) -> u8 {
+        2
+    }
+
+    /// Whether rolls left unused at the end of a turn may be used in
+    /// later turns.
+    fn saves_rolls(&self) -> bool {
+        false
+    }
+
+    /// Points awarded for every yatzy after the first one scored in the
+    /// Yatzy slot. Zero means there is no such bonus.
+    fn yatzy_bonus(&self) -> u16 {
+        0
+    }
+
+    /// The points `dice` would score in `category` given the rest of the
+    /// sheet, or `None` if they do not qualify.
+    fn points(&self, _score: &Score, category: Category, dice: &[usize]) -> Option<u16> {
+        self.validator(category)(dice).map(|points| points as u16)
+    }
+
+    /// Whether the player may score or strike out `category` with `dice`.
+    fn allowed(&self, _score: &Score, _category: Category, _dice: &[usize]) -> bool {
         true
     }
-    fn select_slot(&mut self, dice: &Dice,
-                   validators: [fn(&[usize; 5]) -> Option<usize>; 15],
-                   i: &mut usize) -> SlotSelectStatus {
-
-        // Enter raw mode
-        // Get the standard input stream.
-        let stdin = stdin();
-        // Get the standard output stream and go to raw mode.
-        let mut stdout = stdout().into_raw_mode().unwrap();
-        write!(stdout, "{}", termion::cursor::Hide).unwrap();
-        // Flush stdout (i.e. make the output appear).
-        stdout.flush().unwrap();
-
-        for c in stdin.keys() {
-            match c.unwrap() {
-                Key::Ctrl(c) => {
-                    if c == 'c' {
-                        return SlotSelectStatus::Exit;
-                    }
-                },
-                Key::Char('\n') => {
-                    if self[*i].value != "  ".to_string() {
-                        return SlotSelectStatus::AlreadySelected;
-                    } else if validators[*i](&dice.current) != None {
-                        self[*i].value = format!("{:>2}", &validators[*i](&dice.current).unwrap());
-                        self[*i].selected = true;
-                        stdout.flush().unwrap();
-                        return SlotSelectStatus::Complete;
-                    } else {
-                        self[*i].selected = true;
-                        stdout.flush().unwrap();
-                        return SlotSelectStatus::Invalid;
-                    }
-                },
-                Key::Char('-') => {
-                    if self[*i].value == "  ".to_string() {
-                        self[*i].selected = true;
-                        self[*i].value = format!(" –");
-                        stdout.flush().unwrap();
-                        return SlotSelectStatus::Complete;
-                    }
-                },
-                Key::Up => {
-                    if i > &mut 0 {
-                        *i -= 1;
-                        self[*i].highlighted = true;
-                        self[*i+1].highlighted = false;
-                        return SlotSelectStatus::Incomplete;
-                    } else {
-                        *i = 14;
-                        self[*i].highlighted = true;
-                        self[0].highlighted = false;
-                        return SlotSelectStatus::Incomplete;
-                    }
-                },
-                Key::Left => {
-                    if *i > 0 {
-                        *i -= 1;
-                        self[*i].highlighted = true;
-                        self[*i+1].highlighted = false;
-                        return SlotSelectStatus::Incomplete;
-                    } else {
-                        *i = 14;
-                        self[*i].highlighted = true;
-                        self[0].highlighted = false;
-                        return SlotSelectStatus::Incomplete;
-                    }
-                },
-                Key::Down => {
-                    if *i < 14 {
-                        *i += 1;
-                        self[*i].highlighted = true;
-                        self[*i-1].highlighted = false;
-                        return SlotSelectStatus::Incomplete;
-                    } else {
-                        *i = 0;
-                        self[*i].highlighted = true;
-                        self[14].highlighted = false;
-                        return SlotSelectStatus::Incomplete;
-                    }
-                },
-                Key::Right => {
-                    if *i < 14 {
-                        *i += 1;
-                        self[*i].highlighted = true;
-                        self[*i-1].highlighted = false;
-                        return SlotSelectStatus::Incomplete;
-                    } else {
-                        *i = 0;
-                        self[*i].highlighted = true;
-                        self[14].highlighted = false;
-                        return SlotSelectStatus::Incomplete;
-                    }
-                },
-                _ => continue,
-            }
-        }
 
-        // Flush again.
-        stdout.flush().unwrap();
+    /// The most points `category` can give, found by trying every roll.
+    fn max_points(&self, category: Category) -> u16 {
+        let validator = self.validator(category);
+        outcomes(self.dice()).iter()
+            .filter_map(|roll| validator(roll))
+            .max()
+            .unwrap_or(0) as u16
+    }
+}
+
+/// Scandinavian yatzy as described in the README. This is the default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Yatzy;
+
+impl Yatzy {
+    const CATEGORIES: [Category; 15] = [
+        Category::Ones, Category::Twos, Category::Threes, Category::Fours,
+        Category::Fives, Category::Sixes, Category::OnePair, Category::TwoPairs,
+        Category::ThreeOfAKind, Category::FourOfAKind, Category::SmallStraight,
+        Category::LargeStraight, Category::FullHouse, Category::Chance,
+        Category::Yatzy,
+    ];
+}
+
+impl Ruleset for Yatzy {
+    fn name(&self) -> &'static str {
+        "Yatzy"
+    }
 
-        // Show the cursor again before we exit.
-        write!(stdout, "{}", termion::cursor::Show).unwrap();
-        SlotSelectStatus::Incomplete
+    fn categories(&self) -> &'static [Category] {
+        &Yatzy::CATEGORIES
     }
 
-    pub fn log(self, path: &std::path::PathBuf) {
-        Highscore::log(&path, self);
-        clear_screen();
-        let highscore = Highscore::new(&path);
-        Highscore::print(&highscore);
+    fn validator(&self, category: Category) -> Validator {
+        ScoreValidator::new()[category as usize]
     }
 }
 
+/// American Yahtzee with the forced Joker rules and 100 point bonuses
+/// for extra Yahtzees.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Yahtzee;
+
+impl Yahtzee {
+    const CATEGORIES: [Category; 13] = [
+        Category::Ones, Category::Twos, Category::Threes, Category::Fours,
+        Category::Fives, Category::Sixes, Category::ThreeOfAKind,
+        Category::FourOfAKind, Category::FullHouse, Category::SmallStraight,
+        Category::LargeStraight, Category::Yatzy, Category::Chance,
+    ];
+
+    /// A Yahtzee rolled when the Yahtzee box is already filled in is a
+    /// Joker.
+    fn is_joker(score: &Score, dice: &[usize]) -> bool {
+        ScoreValidator::yatzy(dice).is_some() &&
+            score.get(Category::Yatzy).is_some_and(|slot| !slot.is_empty())
+    }
+}
 
-pub struct Highscore;
+impl Ruleset for Yahtzee {
+    fn name(&self) -> &'static str {
+        "Yahtzee"
+    }
 
-impl Highscore {
+    fn categories(&self) -> &'static [Category] {
+        &Yahtzee::CATEGORIES
+    }
 
-    pub fn new_path() -> PathBuf {
-        let home = dirs::home_dir().expect("No home directory!");
-        let mut path = PathBuf::new();
-        path.push(home);
-        path.push(".config");
-        path.push("rusty-yacht");
-        create_dir_all(&path).expect("Could not create ~/.config/rusty-yacht");
-        path.push("highscore");
-        path
-    }
-
-    pub fn new(path: &std::path::PathBuf) -> Vec<(u32, String, String)> {
-        let file = File::open(&path).unwrap_or_else(|error| {
-            if error.kind() == ErrorKind::NotFound {
-                File::create(&path).unwrap_or_else(|error| {
-                    panic!("Tried to create ~/.config/rusty-yacht/highscore.txt but there was a problem: {:?}", error);
-                })
-            } else {
-                panic!("There was a problem opening the highscore file: {:?}", error);
-            }
-        });
-        let file = BufReader::new(file);
-        let mut highscore = Vec::new();
-        for line in file.lines() {
-            let l = line.unwrap();
-            let mut name = String::new();
-            for c in l.chars() {
-                if c == '#' {
-                    break;
-                }
-                if c == '|' {
-                    break;
-                }
-                name.push(c);
-            }
-            let date_begin = l.len() - 15;
-            let score_begin = l.len() - 3;
-            if name.len() > 0 {
-                let date = String::from(&l[date_begin..score_begin-2]);
-                let score = String::from(&l[score_begin..]);
-                let score: u32 = score.trim().parse()
-                    .expect("Not a number!");
-                highscore.push((score, date, name));
-                highscore.sort();
-                highscore.reverse();
-            }
+    fn validator(&self, category: Category) -> Validator {
+        match category {
+            Category::ThreeOfAKind => ScoreValidator::three_kind_sum,
+            Category::FourOfAKind => ScoreValidator::four_kind_sum,
+            Category::FullHouse => ScoreValidator::full_house_fixed,
+            Category::SmallStraight => ScoreValidator::small_str_any,
+            Category::LargeStraight => ScoreValidator::large_str_any,
+            _ => ScoreValidator::new()[category as usize],
+        }
+    }
+
+    fn label(&self, category: Category) -> &'static str {
+        match category {
+            Category::Ones => "Aces",
+            Category::ThreeOfAKind => "3 of a Kind",
+            Category::FourOfAKind => "4 of a Kind",
+            Category::SmallStraight => "Sm. Straight",
+            Category::LargeStraight => "Lg. Straight",
+            Category::Yatzy => "Yahtzee",
+            _ => category.name(),
         }
-        highscore
     }
 
-    pub fn print(highscore: &Vec<(u32, String, String)>) {
-        println!("╔═══════════════════════════════════════════════╗");
-        println!("║ HIGH-SCORE TABLE                              ║");
-        println!("╠══════════════════════════╦════════════╦═══════╣");
-        println!("║ Name                     ║ Date       ║ Score ║");
-        println!("╟──────────────────────────╫────────────╫───────╢");
+    fn bonus(&self) -> u16 {
+        35
+    }
+
+    fn layout(&self) -> Layout {
+        Layout::American
+    }
+
+    fn yatzy_bonus(&self) -> u16 {
+        100
+    }
 
-        for elem in highscore {
-            match elem.0 {
-                0..=9   => { println!("║ {} ║ {} ║     {} ║", elem.2, elem.1, elem.0); },
-                10..=99 => { println!("║ {} ║ {} ║    {} ║", elem.2, elem.1, elem.0); },
-                _       => { println!("║ {} ║ {} ║   {} ║", elem.2, elem.1, elem.0); },
+    fn points(&self, score: &Score, category: Category, dice: &[usize]) -> Option<u16> {
+        if Yahtzee::is_joker(score, dice) {
+            match category {
+                Category::FullHouse => return Some(25),
+                Category::SmallStraight => return Some(30),
+                Category::LargeStraight => return Some(40),
+                _ => (),
             }
         }
+        self.validator(category)(dice).map(|points| points as u16)
+    }
 
-        println!("╚══════════════════════════╩════════════╩═══════╝");
-    }
-
-    pub fn log(path: &std::path::PathBuf, score: Score) {
-        let mut name = String::new();
-        loop {
-            println!("Input a name to log your score:");
-            stdin().read_line(&mut name)
-                .expect("Failed to read line");
-            name.pop(); // remove trailing newline
-            let namelen = name.chars().count();
-            if namelen < 1 {
-            } else if namelen > 24 {
-                println!("Too long! Max length is 24 characters.\n");
-                name = String::from("");
-            } else {
-                let name_padding = 24 - namelen;
-                for _ in 0..name_padding {
-                    name.push(' ');
-                }
-                break;
-            }
+    /// A Joker must go in the matching upper box if it is open. Otherwise
+    /// any lower box may be used, and only when those are all filled in
+    /// may another upper box be struck out.
+    fn allowed(&self, score: &Score, category: Category, dice: &[usize]) -> bool {
+        if !Yahtzee::is_joker(score, dice) {
+            return true;
         }
-        let mut date = Local::now().date().to_string();
-        date.truncate(10);
-
-        let mut file = OpenOptions::new()
-            .append(true)
-            .open(&path)
-            .unwrap_or_else(|error| {
-                if error.kind() == ErrorKind::NotFound {
-                    File::create(&path).unwrap_or_else(|error| {
-                        panic!("Tried to create ~/.config/rusty-yacht/highscore.txt but there was a problem: {:?}", error);
-                    })
-                } else {
-                    panic!("There was a problem opening the highscore file: {:?}", error);
-                }
-            });
-        let mut total: u16 = 0;
-
-        for elm in score.iter() {
-            if elm.value != "  " &&
-                elm.value != "   " &&
-                elm.value != " –" &&
-                elm.name != "Sum" {
-                    total += elm.value.trim().parse::<u16>().unwrap();
-                }
+        let upper = Category::upper(dice[0]).unwrap();
+        if score.get(upper) == Some(SlotState::Empty) {
+            return category == upper;
         }
-
-        match total {
-            0..=9 => { if let Err(e) = writeln!(file, "{}| {} |  {:?}", name, date, total) {
-                eprintln!("Couldn't write to file: {}", e); }
-            },
-            10..=99 => { if let Err(e) = writeln!(file, "{}| {} | {:?}", name, date, total) {
-                eprintln!("Couldn't write to file: {}", e); }
-            },
-            _ => { if let Err(e) = writeln!(file, "{}| {} |{:?}", name, date, total) {
-                eprintln!("Couldn't write to file: {}", e); }
-            },
+        if category.is_upper() {
+            return score.iter().all(|(c, slot)| c.is_upper() || !slot.is_empty());
         }
+        true
     }
 }
 
-pub struct Dice {
-    pub current: [usize; 5],
-    pub to_keep: [usize; 5],
+/// Maxi Yatzy, played with six dice. Rolls left unused at the end of a
+/// turn are saved for later turns.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MaxiYatzy;
+
+impl MaxiYatzy {
+    const CATEGORIES: [Category; 20] = [
+        Category::Ones, Category::Twos, Category::Threes, Category::Fours,
+        Category::Fives, Category::Sixes, Category::OnePair, Category::TwoPairs,
+        Category::ThreePairs, Category::ThreeOfAKind, Category::FourOfAKind,
+        Category::FiveOfAKind, Category::SmallStraight, Category::LargeStraight,
+        Category::FullStraight, Category::FullHouse, Category::Castle,
+        Category::Tower, Category::Chance, Category::Yatzy,
+    ];
 }
 
-impl Dice {
-    pub fn new() -> Dice {
-        Dice {
-            current: [0,0,0,0,0],
-            to_keep: [0,0,0,0,0],
-        }
+impl Ruleset for MaxiYatzy {
+    fn name(&self) -> &'static str {
+        "Maxi Yatzy"
     }
 
-    pub fn keep_all(&self) -> bool {
-        for &i in self.to_keep.iter() {
-            if i == 0 {
-                return false;
-            }
-        }
-        true
+    fn categories(&self) -> &'static [Category] {
+        &MaxiYatzy::CATEGORIES
     }
 
-    pub fn roll(&mut self) {
-        for (i, &item) in self.to_keep.iter().enumerate() {
-            if item == 0 as usize {
-                self.current[i] = rand::thread_rng().gen_range(1, 7);
-            } else {
-                self.current[i] = item;
-            }
+    fn validator(&self, category: Category) -> Validator {
+        match category {
+            Category::ThreePairs => ScoreValidator::three_pairs,
+            Category::FiveOfAKind => ScoreValidator::five_kind,
+            Category::FullStraight => ScoreValidator::full_str,
+            Category::Castle => ScoreValidator::castle,
+            Category::Tower => ScoreValidator::tower,
+            Category::Yatzy => ScoreValidator::maxi_yatzy,
+            _ => ScoreValidator::new()[category as usize],
         }
     }
 
-    pub fn reroll_all(&mut self) {
-        for die in &mut self.current.iter_mut() {
-            *die = rand::thread_rng().gen_range(1, 7);
-        }
-        for die in &mut self.to_keep {
-            *die = 0 as usize;
-        }
-    }
-
-    pub fn print(&self) {
-        let mut dot: [[char; 15]; 3] = [[' '; 15]; 3];
-
-        for (i, item) in self.current.iter().enumerate() {
-            match item {
-                1 => {
-                    if self.to_keep[i] > 0 {
-                        match i {
-                            0 => dot[1][1] = '●',
-                            1 => dot[1][4] = '●',
-                            2 => dot[1][7] = '●',
-                            3 => dot[1][10] = '●',
-                            _ => dot[1][13] = '●',
-                        }
-                    } else {
-                        match i {
-                            0 => dot[1][1] = '○',
-                            1 => dot[1][4] = '○',
-                            2 => dot[1][7] = '○',
-                            3 => dot[1][10] = '○',
-                            _ => dot[1][13] = '○',
-                        }
-                    }
-                },
-                2 => {
-                    if self.to_keep[i] > 0 {
-                        match i {
-                            0 => { dot[0][1] = '●';
-                                   dot[2][0] = '●'; },
-                            1 => { dot[0][3] = '●';
-                                   dot[2][2] = '●'; },
-                            2 => { dot[0][5] = '●';
-                                   dot[2][4] = '●'; },
-                            3 => { dot[0][7] = '●';
-                                   dot[2][6] = '●'; },
-                            _ => { dot[0][9] = '●';
-                                   dot[2][8] = '●'; },
-                        }
-                    } else {
-                        match i {
-                            0 => { dot[0][1] = '○';
-                                   dot[2][0] = '○'; },
-                            1 => { dot[0][3] = '○';
-                                   dot[2][2] = '○'; },
-                            2 => { dot[0][5] = '○';
-                                   dot[2][4] = '○'; },
-                            3 => { dot[0][7] = '○';
-                                   dot[2][6] = '○'; },
-                            _ => { dot[0][9] = '○';
-                                   dot[2][8] = '○'; },
-                        }
-                    }
-                },
-                3 => {
-                    if self.to_keep[i] > 0 {
-                        match i {
-                            0 => { dot[0][1] = '●';
-                                   dot[1][1] = '●';
-                                   dot[2][0] = '●'; },
-                            1 => { dot[0][3] = '●';
-                                   dot[1][4] = '●';
-                                   dot[2][2] = '●'; },
-                            2 => { dot[0][5] = '●';
-                                   dot[1][7] = '●';
-                                   dot[2][4] = '●'; },
-                            3 => { dot[0][7] = '●';
-                                   dot[1][10] = '●';
-                                   dot[2][6] = '●'; },
-                            _ => { dot[0][9] = '●';
-                                   dot[1][13] = '●';
-                                   dot[2][8] = '●'; },
-                        }
-                    } else {
-                        match i {
-                            0 => { dot[0][1] = '○';
-                                   dot[1][1] = '○';
-                                   dot[2][0] = '○'; },
-                            1 => { dot[0][3] = '○';
-                                   dot[1][4] = '○';
-                                   dot[2][2] = '○'; },
-                            2 => { dot[0][5] = '○';
-                                   dot[1][7] = '○';
-                                   dot[2][4] = '○'; },
-                            3 => { dot[0][7] = '○';
-                                   dot[1][10] = '○';
-                                   dot[2][6] = '○'; },
-                            _ => { dot[0][9] = '○';
-                                   dot[1][13] = '○';
-                                   dot[2][8] = '○'; },
-                        }
-                    }
-                },
-                4 => {
-                    if self.to_keep[i] > 0 {
-                        match i {
-                            0 => { dot[0][0] = '●';
-                                   dot[0][1] = '●';
-                                   dot[2][0] = '●';
-                                   dot[2][1] = '●'; },
-                            1 => { dot[0][2] = '●';
-                                   dot[0][3] = '●';
-                                   dot[2][2] = '●';
-                                   dot[2][3] = '●'; },
-                            2 => { dot[0][4] = '●';
-                                   dot[0][5] = '●';
-                                   dot[2][4] = '●';
-                                   dot[2][5] = '●'; },
-                            3 => { dot[0][6] = '●';
-                                   dot[0][7] = '●';
-                                   dot[2][6] = '●';
-                                   dot[2][7] = '●'; },
-                            _ => { dot[0][8] = '●';
-                                   dot[0][9] = '●';
-                                   dot[2][8] = '●';
-                                   dot[2][9] = '●'; },
-                        }
-                    } else {
-                        match i {
-                            0 => { dot[0][0] = '○';
-                                   dot[0][1] = '○';
-                                   dot[2][0] = '○';
-                                   dot[2][1] = '○'; },
-                            1 => { dot[0][2] = '○';
-                                   dot[0][3] = '○';
-                                   dot[2][2] = '○';
-                                   dot[2][3] = '○'; },
-                            2 => { dot[0][4] = '○';
-                                   dot[0][5] = '○';
-                                   dot[2][4] = '○';
-                                   dot[2][5] = '○'; },
-                            3 => { dot[0][6] = '○';
-                                   dot[0][7] = '○';
-                                   dot[2][6] = '○';
-                                   dot[2][7] = '○'; },
-                            _ => { dot[0][8] = '○';
-                                   dot[0][9] = '○';
-                                   dot[2][8] = '○';
-                                   dot[2][9] = '○'; },
-                        }
-                    }
-                },
-                5 => {
-                    if self.to_keep[i] > 0 {
-                        match i {
-                            0 => { dot[0][0] = '●';
-                                   dot[0][1] = '●';
-                                   dot[1][1] = '●';
-                                   dot[2][0] = '●';
-                                   dot[2][1] = '●';},
-                            1 => { dot[0][2] = '●';
-                                   dot[0][3] = '●';
-                                   dot[1][4] = '●';
-                                   dot[2][2] = '●';
-                                   dot[2][3] = '●'},
-                            2 => { dot[0][4] = '●';
-                                   dot[0][5] = '●';
-                                   dot[1][7] = '●';
-                                   dot[2][4] = '●';
-                                   dot[2][5] = '●';},
-                            3 => { dot[0][6] = '●';
-                                   dot[0][7] = '●';
-                                   dot[1][10] = '●';
-                                   dot[2][6] = '●';
-                                   dot[2][7] = '●';},
-                            _ => { dot[0][8] = '●';
-                                   dot[0][9] = '●';
-                                   dot[1][13] = '●';
-                                   dot[2][8] = '●';
-                                   dot[2][9] = '●';},
-                        }
-                    } else {
-                        match i {
-                            0 => { dot[0][0] = '○';
-                                   dot[0][1] = '○';
-                                   dot[1][1] = '○';
-                                   dot[2][0] = '○';
-                                   dot[2][1] = '○';},
-                            1 => { dot[0][2] = '○';
-                                   dot[0][3] = '○';
-                                   dot[1][4] = '○';
-                                   dot[2][2] = '○';
-                                   dot[2][3] = '○'},
-                            2 => { dot[0][4] = '○';
-                                   dot[0][5] = '○';
-                                   dot[1][7] = '○';
-                                   dot[2][4] = '○';
-                                   dot[2][5] = '○';},
-                            3 => { dot[0][6] = '○';
-                                   dot[0][7] = '○';
-                                   dot[1][10] = '○';
-                                   dot[2][6] = '○';
-                                   dot[2][7] = '○';},
-                            _ => { dot[0][8] = '○';
-                                   dot[0][9] = '○';
-                                   dot[1][13] = '○';
-                                   dot[2][8] = '○';
-                                   dot[2][9] = '○';},
-                        }
-                    }
-                },
-                _ => {
-                    if self.to_keep[i] > 0 {
-                        match i {
-                            0 => { dot[0][0] = '●';
-                                   dot[0][1] = '●';
-                                   dot[1][0] = '●';
-                                   dot[1][2] = '●';
-                                   dot[2][0] = '●';
-                                   dot[2][1] = '●'; },
-                            1 => { dot[0][2] = '●';
-                                   dot[0][3] = '●';
-                                   dot[1][3] = '●';
-                                   dot[1][5] = '●';
-                                   dot[2][2] = '●';
-                                   dot[2][3] = '●'; },
-                            2 => { dot[0][4] = '●';
-                                   dot[0][5] = '●';
-                                   dot[1][6] = '●';
-                                   dot[1][8] = '●';
-                                   dot[2][4] = '●';
-                                   dot[2][5] = '●'; },
-                            3 => { dot[0][6] = '●';
-                                   dot[0][7] = '●';
-                                   dot[1][9] = '●';
-                                   dot[1][11] = '●';
-                                   dot[2][6] = '●';
-                                   dot[2][7] = '●'; },
-                            _ => { dot[0][8] = '●';
-                                   dot[0][9] = '●';
-                                   dot[1][12] = '●';
-                                   dot[1][14] = '●';
-                                   dot[2][8] = '●';
-                                   dot[2][9] = '●'; },
-                        }
-                    } else {
-                        match i {
-                            0 => { dot[0][0] = '○';
-                                   dot[0][1] = '○';
-                                   dot[1][0] = '○';
-                                   dot[1][2] = '○';
-                                   dot[2][0] = '○';
-                                   dot[2][1] = '○'; },
-                            1 => { dot[0][2] = '○';
-                                   dot[0][3] = '○';
-                                   dot[1][3] = '○';
-                                   dot[1][5] = '○';
-                                   dot[2][2] = '○';
-                                   dot[2][3] = '○'; },
-                            2 => { dot[0][4] = '○';
-                                   dot[0][5] = '○';
-                                   dot[1][6] = '○';
-                                   dot[1][8] = '○';
-                                   dot[2][4] = '○';
-                                   dot[2][5] = '○'; },
-                            3 => { dot[0][6] = '○';
-                                   dot[0][7] = '○';
-                                   dot[1][9] = '○';
-                                   dot[1][11] = '○';
-                                   dot[2][6] = '○';
-                                   dot[2][7] = '○'; },
-                            _ => { dot[0][8] = '○';
-                                   dot[0][9] = '○';
-                                   dot[1][12] = '○';
-                                   dot[1][14] = '○';
-                                   dot[2][8] = '○';
-                                   dot[2][9] = '○'; },
-                        }
-                    }
-                }
-            }
+    fn label(&self, category: Category) -> &'static str {
+        match category {
+            Category::Yatzy => "Maxi Yatzy",
+            _ => category.name(),
         }
+    }
 
-        println!("╔═══════╗ ╔═══════╗ ╔═══════╗ ╔═══════╗ ╔═══════╗");
-        println!("║ {}   {} ║ ║ {}   {} ║ ║ {}   {} ║ ║ {}   {} ║ ║ {}   {} ║",
-                 dot[0][0], dot[0][1], dot[0][2], dot[0][3], dot[0][4],
-                 dot[0][5], dot[0][6], dot[0][7], dot[0][8], dot[0][9]);
-        println!("║ {} {} {} ║ ║ {} {} {} ║ ║ {} {} {} ║ ║ {} {} {} ║ ║ {} {} {} ║",
-                 dot[1][0], dot[1][1], dot[1][2], dot[1][3], dot[1][4],
-                 dot[1][5], dot[1][6], dot[1][7], dot[1][8], dot[1][9],
-                 dot[1][10], dot[1][11], dot[1][12], dot[1][13], dot[1][14]);
-        println!("║ {}   {} ║ ║ {}   {} ║ ║ {}   {} ║ ║ {}   {} ║ ║ {}   {} ║",
-                 dot[2][0], dot[2][1], dot[2][2], dot[2][3], dot[2][4],
-                 dot[2][5], dot[2][6], dot[2][7], dot[2][8], dot[2][9]);
-        println!("╚═══════╝ ╚═══════╝ ╚═══════╝ ╚═══════╝ ╚═══════╝");
-    }
-
-    pub fn select(&mut self, score: &mut Score, count: &i32) {
-        let mut selected: [usize; 5] = [0, 0, 0, 0, 0];
-        let mut left_margin = "".to_string();
-        let mut margin_width: usize = 0;
-        loop {
-            clear_screen();
-            if *count == 2 {
-                println!("  Use the arrow keys and Space to toggle which\n  dice to keep. Then press Enter to reroll\n  for the last time.");
-            } else {
-                println!("  Use the arrow keys and Space to toggle which\n  dice to keep. Then press Enter to reroll.");
+    fn bonus_threshold(&self) -> u16 {
+        84
+    }
 
-            }
-            score.print();
-            self.print();
-            match self.select_checker(&mut left_margin, &mut margin_width,
-                                      &mut selected) {
-                DiceSelectStatus::Exit => std::process::exit(0),
-                DiceSelectStatus::Complete => break,
-                DiceSelectStatus::Incomplete => continue,
-            };
-        }
-    }
-    fn select_checker(&mut self, left_margin: &mut String,
-                      margin_width: &mut usize, selected: &mut [usize; 5])
-                      -> DiceSelectStatus {
-
-        // Enter raw mode
-        // Get the standard input stream.
-        let stdin = stdin();
-        // Get the standard output stream and go to raw mode.
-        let mut stdout = stdout().into_raw_mode().unwrap();
-        write!(stdout, "{}", termion::cursor::Hide).unwrap();
-
-        let term_size = match termion::terminal_size() {
-            Ok(num) => num,
-            Err(_) => (151, 38), // In case of error, set this value
-        };
+    fn dice(&self) -> usize {
+        6
+    }
 
-        let bottom_line = term_size.1;
-
-        // Flush stdout (i.e. make the output appear).
-        stdout.flush().unwrap();
-        println!("{}●━━━━━━━●{}", *left_margin, termion::cursor::Goto(1, bottom_line -4));
-
-        for c in stdin.keys() {
-            // Clear the current line.
-            write!(stdout, "{}{}", termion::cursor::Goto(1, bottom_line), termion::clear::CurrentLine).unwrap();
-
-            match c.unwrap() {
-                Key::Ctrl(c) => { if c == 'c' { return DiceSelectStatus::Exit; } },
-                Key::Char('\n') => { selected[*margin_width] = 1;
-                                     return DiceSelectStatus::Complete;},
-                Key::Char(' ')  => { println!("{}●━━━━━━━●{}", *left_margin, termion::cursor::Goto(1, bottom_line -4));
-                                     if selected[*margin_width] == 0 && self.to_keep[*margin_width] == 0 {
-                                         self.to_keep[*margin_width] = self.current[*margin_width];
-                                         selected[*margin_width] = 1;
-                                         return DiceSelectStatus::Incomplete;
-                                     } else {
-                                         self.to_keep[*margin_width] = 0;
-                                         selected[*margin_width] = 0;
-                                         return DiceSelectStatus::Incomplete;
-                                     } },
-                Key::Left      => { if *margin_width > 0 { *margin_width = *margin_width -1};
-                                    if *margin_width == 0 {
-                                        *left_margin = "".to_string();
-                                    } else if *margin_width == 1 {
-                                        *left_margin = "          ".to_string();
-                                    } else if *margin_width == 2 {
-                                        *left_margin = "                    ".to_string();
-                                    } else if *margin_width == 3 {
-                                        *left_margin = "                              ".to_string();
-                                    } else if *margin_width == 4 {
-                                        *left_margin = "                                        ".to_string();
-                                    }
-                                    println!("{}●━━━━━━━●{}", *left_margin, termion::cursor::Goto(1, bottom_line -4)); },
-                Key::Down      => { if *margin_width > 0 { *margin_width = *margin_width -1};
-                                    if *margin_width == 0 {
-                                        *left_margin = "".to_string();
-                                    } else if *margin_width == 1 {
-                                        *left_margin = "          ".to_string();
-                                    } else if *margin_width == 2 {
-                                        *left_margin = "                    ".to_string();
-                                    } else if *margin_width == 3 {
-                                        *left_margin = "                              ".to_string();
-                                    } else if *margin_width == 4 {
-                                        *left_margin = "                                        ".to_string();
-                                    }
-                                    println!("{}●━━━━━━━●{}", *left_margin, termion::cursor::Goto(1, bottom_line -4)); },
-                Key::Right     => { if *margin_width < 4 { *margin_width += 1};
-                                    if *margin_width == 0 {
-                                        *left_margin = "".to_string();
-                                    } else if *margin_width == 1 {
-                                        *left_margin = "          ".to_string();
-                                    } else if *margin_width == 2 {
-                                        *left_margin = "                    ".to_string();
-                                    } else if *margin_width == 3 {
-                                        *left_margin = "                              ".to_string();
-                                    } else if *margin_width == 4 {
-                                        *left_margin = "                                        ".to_string();
-                                    }
-                                    println!("{}●━━━━━━━●{}", *left_margin, termion::cursor::Goto(1, bottom_line -4)); },
-                Key::Up     => { if *margin_width < 4 { *margin_width += 1};
-                                 if *margin_width == 0 {
-                                     *left_margin = "".to_string();
-                                 } else if *margin_width == 1 {
-                                     *left_margin = "          ".to_string();
-                                 } else if *margin_width == 2 {
-                                     *left_margin = "                    ".to_string();
-                                 } else if *margin_width == 3 {
-                                     *left_margin = "                              ".to_string();
-                                 } else if *margin_width == 4 {
-                                     *left_margin = "                                        ".to_string();
-                                 }
-                                 println!("{}●━━━━━━━●{}", *left_margin, termion::cursor::Goto(1, bottom_line -4)); },
-                _              => continue,
-            }
+    fn saves_rolls(&self) -> bool {
+        true
+    }
+}
 
-            // Flush again.
-            stdout.flush().unwrap();
-        }
+/// Every ruleset the game knows about, the default first.
+pub fn rulesets() -> Vec<Box<dyn Ruleset>> {
+    vec![Box::new(Yatzy), Box::new(Yahtzee), Box::new(MaxiYatzy)]
+}
 
-        // Show the cursor again before we exit.
-        write!(stdout, "{}", termion::cursor::Show).unwrap();
-        DiceSelectStatus::Incomplete
-    }
+/// Looks up a ruleset by its id, ignoring case.
+pub fn ruleset_by_name(name: &str) -> Option<Box<dyn Ruleset>> {
+    rulesets().into_iter()
+        .find(|ruleset| ruleset.id().eq_ignore_ascii_case(name))
 }
 
-pub fn welcome() {
-    println!("R U S T Y R U S T Y R U S T Y R U S T Y R U S T Y");
-    println!("U       A U       A U       A U       A U       A");
-    println!("S       C S       C S       S T       C S       C");
-    println!("T       H T       H T       H S       H T       H");
-    println!("Y A C H T Y A C H T Y A C H T Y A C H T Y A C H T\n");
-
-    let stdin = stdin();
-    let mut stdout = stdout().into_raw_mode().unwrap();
-    write!(stdout, "{}", termion::cursor::Hide).unwrap();
-    for c in stdin.keys() {
-        match c.unwrap() {
-            Key::Char('\n') => break,
-            Key::Ctrl(c) => if c == 'c' {
-                stdout.suspend_raw_mode().unwrap();
-                std::process::exit(0);
-            },
-            _ => continue,
+/// Every distinct roll of `count` dice, each sorted in ascending order.
+pub fn outcomes(count: usize) -> Vec<Vec<usize>> {
+    let mut rolls = vec![Vec::new()];
+    for _ in 0..count {
+        let mut longer = Vec::new();
+        for roll in &rolls {
+            let lowest = roll.last().cloned().unwrap_or(1);
+            for die in lowest..7 {
+                let mut next = roll.clone();
+                next.push(die);
+                longer.push(next);
+            }
         }
+        rolls = longer;
     }
+    rolls
+}
 
-    stdout.flush().unwrap();
+/// A player's score sheet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Score {
+    slots: Vec<(Category, SlotState)>,
+    bonus_threshold: u16,
+    bonus: u16,
+    yatzy_bonus: u16,
 }
 
-pub fn clear_screen() {
-    println!("{}", clear::All);
+impl Default for Score {
+    fn default() -> Score {
+        Score::new()
+    }
+}
+
+impl Score {
+    pub fn new() -> Score {
+        Score::for_ruleset(&Yatzy)
+    }
+
+    pub fn for_ruleset(ruleset: &dyn Ruleset) -> Score {
+        Score {
+            slots: ruleset.categories().iter()
+                .map(|&category| (category, SlotState::Empty))
+                .collect(),
+            bonus_threshold: ruleset.bonus_threshold(),
+            bonus: ruleset.bonus(),
+            yatzy_bonus: 0,
+        }
+    }
+
+    /// Returns the slot for `category`, or `None` if the sheet lacks it.
+    pub fn get(&self, category: Category) -> Option<SlotState> {
+        self.slots.iter()
+            .find(|&&(c, _)| c == category)
+            .map(|&(_, slot)| slot)
+    }
+
+    fn set(&mut self, category: Category, state: SlotState) {
+        for slot in self.slots.iter_mut() {
+            if slot.0 == category {
+                slot.1 = state;
+            }
+        }
+    }
+
+    /// Iterates over every category and its slot, top to bottom.
+    pub fn iter(&self) -> impl Iterator<Item = (Category, SlotState)> + '_ {
+        self.slots.iter().cloned()
+    }
+
+    pub fn is_final(&self) -> bool {
+        self.slots.iter().all(|&(_, slot)| !slot.is_empty())
+    }
+
+    /// Sum of the upper section.
+    pub fn sum(&self) -> u16 {
+        self.iter()
+            .filter(|&(category, _)| category.is_upper())
+            .map(|(_, slot)| slot.points())
+            .sum()
+    }
+
+    /// The bonus slot: scored once the upper section reaches the
+    /// threshold, struck once it is full without doing so.
+    pub fn bonus(&self) -> SlotState {
+        if self.sum() >= self.bonus_threshold {
+            SlotState::Scored(self.bonus)
+        } else if self.iter().all(|(category, slot)| !category.is_upper() || !slot.is_empty()) {
+            SlotState::Struck
+        } else {
+            SlotState::Empty
+        }
+    }
+
+    /// Points from extra yatzies, for rulesets that award them.
+    pub fn yatzy_bonus(&self) -> u16 {
+        self.yatzy_bonus
+    }
+
+    pub fn total(&self) -> u16 {
+        self.slots.iter().map(|&(_, slot)| slot.points()).sum::<u16>() +
+            self.bonus().points() + self.yatzy_bonus
+    }
+}
+
+/// The high-score table, kept in a text file with one result per line
+/// after a header with the version of the format:
+///
+/// ```text
+/// rusty-yacht highscore 2
+/// 2019-05-01T20:15:09 yatzy 248 Ones=3,Twos=6,...,Yatzy=50 Albin
+/// ```
+///
+/// The fields are the time, the ruleset, the total, what every slot held
+/// (`-` if struck out) and the name, which is the rest of the line.
+/// Results migrated from the first format have `-` for ruleset and slots.
+pub struct Highscore;
+
+/// One result on the high-score table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub name: String,
+    pub time: NaiveDateTime,
+    pub score: u32,
+    /// Id of the ruleset, or `None` for results from before it was kept.
+    pub ruleset: Option<String>,
+    /// What every slot held at the end, empty if it is not known.
+    pub slots: Vec<(Category, SlotState)>,
+}
+
+/// The first line of the high-score file, with the version of the format.
+const HIGHSCORE_HEADER: &str = "rusty-yacht highscore 2";
+
+/// What a high-score file holds.
+enum HighscoreFormat {
+    /// Nothing, or no file at all.
+    Empty,
+    Current,
+    /// The first, fixed-width format, without a header.
+    V1,
+}
+
+/// A high-score file that `Highscore::log` converted to the current format.
+#[derive(Debug)]
+pub struct Migration {
+    /// Where the file in the old format was moved.
+    pub backup: PathBuf,
+    /// Lines of the old file that could not be read and were left out.
+    pub skipped: usize,
+}
+
+/// Where the game keeps its files, ~/.config/rusty-yacht, created if it is
+/// not there.
+pub fn config_dir() -> Result<PathBuf, Error> {
+    let mut path = dirs::home_dir().ok_or(Error::NoHomeDir)?;
+    path.push(".config");
+    path.push("rusty-yacht");
+    create_dir_all(&path)?;
+    Ok(path)
+}
+
+impl Entry {
+    /// The result of `score` in the ruleset with id `ruleset`, now.
+    pub fn new(name: &str, ruleset: &str, score: &Score) -> Entry {
+        Entry {
+            name: name.to_string(),
+            time: Local::now().naive_local().with_nanosecond(0).unwrap(),
+            score: u32::from(score.total()),
+            ruleset: Some(ruleset.to_string()),
+            slots: score.iter().collect(),
+        }
+    }
+
+    /// Whether this is a result in the ruleset with id `ruleset`.
+    pub fn in_ruleset(&self, ruleset: &str) -> bool {
+        self.ruleset.as_deref() == Some(ruleset)
+    }
+}
+
+impl Highscore {
+
+    pub fn new_path() -> Result<PathBuf, Error> {
+        Ok(config_dir()?.join("highscore"))
+    }
+
+    /// Reads the results at `path`, best first, and how many lines could
+    /// not be read and were skipped. A file in the first, fixed-width
+    /// format is read as it is; it is converted by the next `log`.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(path: &PathBuf) -> Result<(Vec<Entry>, usize), Error> {
+        let (mut highscore, skipped, _) = Highscore::read(path)?;
+        highscore.sort_by(|a, b| b.score.cmp(&a.score).then(b.time.cmp(&a.time)));
+        Ok((highscore, skipped))
+    }
+
+    /// Appends `entry` to the highscore file. A file in the first format is
+    /// converted first, keeping the original next to it with the extension
+    /// `v1`, or `v1.1` and so on if that is taken; if so, returns where it
+    /// went.
+    pub fn log(path: &PathBuf, entry: &Entry) -> Result<Option<Migration>, Error> {
+        let (highscore, skipped, format) = Highscore::read(path)?;
+        let migration = match format {
+            HighscoreFormat::Current => None,
+            // Missing, or what is left of a first log that failed; start it
+            // over in the current format.
+            HighscoreFormat::Empty => {
+                writeln!(File::create(path)?, "{}", HIGHSCORE_HEADER)?;
+                None
+            },
+            HighscoreFormat::V1 => Some(Migration { backup: Highscore::migrate(path, &highscore)?, skipped }),
+        };
+        let mut file = OpenOptions::new().append(true).open(path)?;
+        writeln!(file, "{}", Highscore::format(entry))?;
+        Ok(migration)
+    }
+
+    /// The results at `path` in the order of the file, how many lines were
+    /// skipped and the format of the file.
+    fn read(path: &PathBuf) -> Result<(Vec<Entry>, usize, HighscoreFormat), Error> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(ref error) if error.kind() == ErrorKind::NotFound => {
+                return Ok((Vec::new(), 0, HighscoreFormat::Empty));
+            },
+            Err(error) => return Err(error.into()),
+        };
+        let lines = BufReader::new(file).lines().collect::<io::Result<Vec<String>>>()?;
+        if lines.iter().all(|line| line.trim().is_empty()) {
+            return Ok((Vec::new(), 0, HighscoreFormat::Empty));
+        }
+        let current = lines.first().map(String::as_str) == Some(HIGHSCORE_HEADER);
+        let mut highscore = Vec::new();
+        let mut skipped = 0;
+        for line in lines.iter().skip(if current { 1 } else { 0 }) {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry = if current { Highscore::parse(line) } else { Highscore::parse_v1(line) };
+            match entry {
+                Some(entry) => highscore.push(entry),
+                None => skipped += 1,
+            }
+        }
+        let format = if current { HighscoreFormat::Current } else { HighscoreFormat::V1 };
+        Ok((highscore, skipped, format))
+    }
+
+    /// Rewrites the file at `path` in the current format, moving the old
+    /// one aside without replacing an earlier backup. Returns where the old
+    /// one went.
+    fn migrate(path: &PathBuf, highscore: &[Entry]) -> Result<PathBuf, Error> {
+        let mut backup = path.with_extension("v1");
+        let mut n = 0;
+        while backup.exists() {
+            n += 1;
+            backup = path.with_extension(format!("v1.{}", n));
+        }
+        rename(path, &backup)?;
+        let mut file = File::create(path)?;
+        writeln!(file, "{}", HIGHSCORE_HEADER)?;
+        for entry in highscore {
+            writeln!(file, "{}", Highscore::format(entry))?;
+        }
+        Ok(backup)
+    }
+
+    fn format(entry: &Entry) -> String {
+        let slots: Vec<String> = entry.slots.iter()
+            .map(|&(category, slot)| match slot {
+                SlotState::Scored(points) => format!("{:?}={}", category, points),
+                _ => format!("{:?}=-", category),
+            })
+            .collect();
+        let slots = if slots.is_empty() { "-".to_string() } else { slots.join(",") };
+        format!("{} {} {} {} {}", entry.time.format("%Y-%m-%dT%H:%M:%S"),
+                entry.ruleset.as_deref().unwrap_or("-"), entry.score, slots, entry.name)
+    }
+
+    fn parse(line: &str) -> Option<Entry> {
+        let fields: Vec<&str> = line.splitn(5, ' ').collect();
+        if fields.len() != 5 {
+            return None;
+        }
+        let slots = match fields[3] {
+            "-" => Vec::new(),
+            slots => slots.split(',')
+                .map(|slot| {
+                    let mut parts = slot.splitn(2, '=');
+                    let name = parts.next()?;
+                    let category = Category::ALL.iter().cloned()
+                        .find(|category| format!("{:?}", category) == name)?;
+                    match parts.next()? {
+                        "-" => Some((category, SlotState::Struck)),
+                        points => points.parse().ok().map(|points| (category, SlotState::Scored(points))),
+                    }
+                })
+                .collect::<Option<Vec<_>>>()?,
+        };
+        Some(Entry {
+            name: fields[4].to_string(),
+            time: NaiveDateTime::parse_from_str(fields[0], "%Y-%m-%dT%H:%M:%S").ok()?,
+            score: fields[2].parse().ok()?,
+            // Results converted without a ruleset came from yatzy, the only
+            // one there was.
+            ruleset: Some(if fields[1] == "-" { Yatzy.id() } else { fields[1].to_string() }),
+            slots,
+        })
+    }
+
+    /// Reads a line of the first format, `name padded to 24| date | score`.
+    /// Only yatzy could be played back then.
+    fn parse_v1(line: &str) -> Option<Entry> {
+        let fields: Vec<&str> = line.rsplitn(3, '|').collect();
+        if fields.len() != 3 {
+            return None;
+        }
+        let date = NaiveDate::parse_from_str(fields[1].trim(), "%Y-%m-%d").ok()?;
+        let name = fields[2].trim_end();
+        if name.is_empty() {
+            return None;
+        }
+        Some(Entry {
+            name: name.to_string(),
+            time: date.and_hms_opt(0, 0, 0)?,
+            score: fields[0].trim().parse().ok()?,
+            ruleset: Some(Yatzy.id()),
+            slots: Vec::new(),
+        })
+    }
+}
+
+/// The daily challenge, which gives everybody the same dice on the same
+/// day and keeps its own leaderboard next to the highscore file.
+pub struct Daily;
+
+impl Daily {
+    pub fn today() -> NaiveDate {
+        Local::now().date_naive()
+    }
+
+    /// The seed for `date`: the date written as a number, e.g. 20190501,
+    /// scrambled so that the seed of a day is not plain to see.
+    pub fn seed(date: NaiveDate) -> u64 {
+        let day = date.year() as u64 * 10000 + date.month() as u64 * 100 + date.day() as u64;
+        // SplitMix64, which spreads neighbouring days far apart.
+        let mut z = day.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    pub fn new_path() -> Result<PathBuf, Error> {
+        Ok(config_dir()?.join("daily"))
+    }
+
+    /// The results of `date` in the ruleset with id `ruleset`, best first.
+    pub fn results(path: &PathBuf, date: NaiveDate, ruleset: &str) -> Result<Vec<(u32, String)>, Error> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(ref error) if error.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(error.into()),
+        };
+        let date = date.format("%Y-%m-%d").to_string();
+        let lines = BufReader::new(file).lines().collect::<io::Result<Vec<String>>>()?;
+        let mut results: Vec<(u32, String)> = lines.into_iter()
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.splitn(4, '|').collect();
+                match fields[..] {
+                    [day, id, score, name] if day == date && id == ruleset => {
+                        score.parse().ok().map(|score| (score, name.to_string()))
+                    },
+                    _ => None,
+                }
+            })
+            .collect();
+        results.sort();
+        results.reverse();
+        Ok(results)
+    }
+
+    /// Adds the result of `name` to the leaderboard of `date`, unless they
+    /// are on it already, since only the first game of the day counts.
+    /// Returns whether it was added.
+    pub fn log(path: &PathBuf, date: NaiveDate, ruleset: &str, name: &str, score: u32) -> Result<bool, Error> {
+        let played = Daily::results(path, date, ruleset)?.iter()
+            .any(|(_, player)| player.eq_ignore_ascii_case(name));
+        if played {
+            return Ok(false);
+        }
+        let mut file = OpenOptions::new().append(true).create(true).open(path)?;
+        writeln!(file, "{}|{}|{}|{}", date.format("%Y-%m-%d"), ruleset, score, name)?;
+        Ok(true)
+    }
+}
+
+pub struct Dice {
+    pub current: Vec<usize>,
+    pub to_keep: Vec<usize>,
+    rng: Box<dyn RngCore>,
+}
+
+impl Default for Dice {
+    fn default() -> Dice {
+        Dice::new()
+    }
+}
+
+impl Dice {
+    pub fn new() -> Dice {
+        Dice::with_count(5)
+    }
+
+    pub fn with_count(count: usize) -> Dice {
+        Dice::seeded(count, rand::random())
+    }
+
+    /// Dice that roll the same sequence every time for the same seed.
+    pub fn seeded(count: usize, seed: u64) -> Dice {
+        Dice::with_rng(count, Box::new(StdRng::seed_from_u64(seed)))
+    }
+
+    pub fn with_rng(count: usize, rng: Box<dyn RngCore>) -> Dice {
+        Dice {
+            current: vec![0; count],
+            to_keep: vec![0; count],
+            rng,
+        }
+    }
+
+    pub fn keep_all(&self) -> bool {
+        for &i in self.to_keep.iter() {
+            if i == 0 {
+                return false;
+            }
+        }
+        true
+    }
+
+    pub fn roll(&mut self) {
+        // Every die is drawn, kept or not, so that what the other dice
+        // show does not depend on which ones are kept.
+        for (i, &item) in self.to_keep.iter().enumerate() {
+            let value = self.rng.gen_range(1, 7);
+            self.current[i] = if item == 0 { value } else { item };
+        }
+    }
+
+    pub fn reroll_all(&mut self) {
+        for die in &mut self.current.iter_mut() {
+            *die = self.rng.gen_range(1, 7);
+        }
+        for die in &mut self.to_keep {
+            *die = 0;
+        }
+    }
+}
+
+/// A step of a game, as kept in its history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Move {
+    /// The dice were rolled, leaving the `kept` ones as they were.
+    Roll { kept: Vec<bool>, dice: Vec<usize> },
+    /// The dice were scored in a slot.
+    Place(Category),
+    /// A slot was struck out.
+    Strike(Category),
+}
+
+/// The reasons a `GameState` may refuse an action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameError {
+    /// Every slot has been filled in.
+    GameOver,
+    /// Every reroll of the turn has been used.
+    NoRollsLeft,
+    /// The dice must be rolled before they can be kept or scored.
+    NotRolled,
+    /// There is no die with the given index.
+    NoSuchDie(usize),
+    /// The ruleset has no such slot.
+    NoSuchCategory(Category),
+    /// The slot has already been scored or struck out.
+    AlreadySelected,
+    /// The dice do not qualify for the slot; it can only be struck out.
+    Invalid,
+    /// The ruleset requires the dice to go in another slot.
+    NotAllowed,
+    /// Undo is off, or there is nothing since the last roll to undo.
+    NothingToUndo,
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            GameError::GameOver => write!(f, "the game is over"),
+            GameError::NoRollsLeft => write!(f, "no rolls left this turn"),
+            GameError::NotRolled => write!(f, "the dice have not been rolled yet"),
+            GameError::NoSuchDie(i) => write!(f, "there is no die number {}", i),
+            GameError::NoSuchCategory(c) => write!(f, "there is no {} slot", c.name()),
+            GameError::AlreadySelected => write!(f, "the slot has already been used"),
+            GameError::Invalid => write!(f, "the dice do not qualify for the slot"),
+            GameError::NotAllowed => write!(f, "the rules require another slot"),
+            GameError::NothingToUndo => write!(f, "there is nothing to undo"),
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
+
+/// What can go wrong outside the rules: finding the files the game keeps,
+/// reading and writing them, reading the settings, and talking to the
+/// terminal.
+#[derive(Debug)]
+pub enum Error {
+    /// There is no home directory to keep ~/.config/rusty-yacht in.
+    NoHomeDir,
+    Io(io::Error),
+    /// The config file at the path has the problems listed.
+    Config(PathBuf, Vec<String>),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::NoHomeDir => write!(f, "there is no home directory"),
+            Error::Io(ref error) => write!(f, "{}", error),
+            Error::Config(ref path, ref problems) => {
+                write!(f, "there are problems in {}:\n  {}", path.display(), problems.join("\n  "))
+            },
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Error::Io(ref error) => Some(error),
+            Error::NoHomeDir | Error::Config(..) => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Error {
+        Error::Io(error)
+    }
+}
+
+/// What an action changed, to go back to on undo.
+#[derive(Debug, Clone)]
+struct Snapshot {
+    dice: Vec<usize>,
+    to_keep: Vec<usize>,
+    players: Vec<Player>,
+    current: usize,
+    rolls: u8,
+    turns: usize,
+    history: usize,
+}
+
+/// A seat in the game with its own score sheet.
+#[derive(Debug, Clone)]
+pub struct Player {
+    name: String,
+    score: Score,
+    saved_rolls: u8,
+    strategy: Option<Strategy>,
+}
+
+impl Player {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// How the computer plays this seat, or `None` for a human.
+    pub fn strategy(&self) -> Option<Strategy> {
+        self.strategy
+    }
+
+    pub fn score(&self) -> &Score {
+        &self.score
+    }
+}
+
+/// A game of yatzy for one or more players taking turns, free of any
+/// terminal input or output.
+///
+/// The state only allows legal moves: the dice may be rolled once plus
+/// the rerolls of the ruleset, after which a slot must be scored or
+/// struck out, which passes the dice to the next player.
+pub struct GameState {
+    ruleset: Box<dyn Ruleset>,
+    dice: Dice,
+    players: Vec<Player>,
+    current: usize,
+    rolls: u8,
+    forced: bool,
+    seed: u64,
+    /// Whether the seed was picked by the player rather than at random.
+    seed_chosen: bool,
+    daily: Option<NaiveDate>,
+    /// Turns played by all players together.
+    turns: usize,
+    history: Vec<Move>,
+    undo_limit: usize,
+    undo: VecDeque<Snapshot>,
+}
+
+impl Default for GameState {
+    fn default() -> GameState {
+        GameState::new()
+    }
+}
+
+impl GameState {
+    pub fn new() -> GameState {
+        GameState::with_ruleset(Box::new(Yatzy))
+    }
+
+    /// A solitaire game of `ruleset`.
+    pub fn with_ruleset(ruleset: Box<dyn Ruleset>) -> GameState {
+        GameState::with_players(ruleset, vec![String::new()])
+    }
+
+    /// A game with one human player per name, taking turns in that order.
+    pub fn with_players(ruleset: Box<dyn Ruleset>, names: Vec<String>) -> GameState {
+        let seats = names.into_iter().map(|name| (name, None)).collect();
+        GameState::with_seats(ruleset, seats)
+    }
+
+    /// A game with a player per seat, each a name and the strategy of a
+    /// computer player or `None` for a human.
+    pub fn with_seats(ruleset: Box<dyn Ruleset>, seats: Vec<(String, Option<Strategy>)>) -> GameState {
+        assert!(!seats.is_empty(), "a game needs at least one player");
+        let players = seats.into_iter()
+            .map(|(name, strategy)| Player {
+                name,
+                score: Score::for_ruleset(&*ruleset),
+                saved_rolls: 0,
+                strategy,
+            })
+            .collect();
+        let seed = rand::random();
+        GameState {
+            dice: Dice::with_count(ruleset.dice()),
+            ruleset,
+            players,
+            current: 0,
+            rolls: 0,
+            forced: false,
+            seed,
+            seed_chosen: false,
+            daily: None,
+            turns: 0,
+            history: Vec::new(),
+            undo_limit: 0,
+            undo: VecDeque::new(),
+        }
+    }
+
+    /// A new game with the same ruleset, players and mode.
+    pub fn rematch(&self) -> GameState {
+        // Games are made with the rulesets of `rulesets()`, so the id is known.
+        let ruleset = ruleset_by_name(&self.ruleset.id()).expect("not a known ruleset");
+        let seats = self.players.iter()
+            .map(|player| (player.name.clone(), player.strategy))
+            .collect();
+        let mut game = GameState::with_seats(ruleset, seats);
+        game.forced = self.forced;
+        game
+    }
+
+    pub fn ruleset(&self) -> &dyn Ruleset {
+        &*self.ruleset
+    }
+
+    /// Turns forced play on or off. In forced yatzy every turn must be
+    /// scored or struck out in the next open slot, top to bottom.
+    pub fn set_forced(&mut self, forced: bool) {
+        self.forced = forced;
+    }
+
+    pub fn is_forced(&self) -> bool {
+        self.forced
+    }
+
+    /// The first open slot from the top, which is where the turn has to go
+    /// in forced play.
+    pub fn next_slot(&self) -> Option<Category> {
+        self.score().iter()
+            .find(|&(_, slot)| slot.is_empty())
+            .map(|(category, _)| category)
+    }
+
+    pub fn dice(&self) -> &Dice {
+        &self.dice
+    }
+
+    pub fn players(&self) -> &[Player] {
+        &self.players
+    }
+
+    /// Index of the player whose turn it is.
+    pub fn current_player(&self) -> usize {
+        self.current
+    }
+
+    /// The score sheet of the player whose turn it is.
+    pub fn score(&self) -> &Score {
+        &self.players[self.current].score
+    }
+
+    /// Number of times the dice have been rolled this turn.
+    pub fn rolls(&self) -> u8 {
+        self.rolls
+    }
+
+    /// The seed of the dice. Each turn gets its own sequence of rolls from
+    /// the seed, so every game with the same seed gets the same first roll
+    /// on a turn, and the same rerolls for the same dice kept. In the daily
+    /// challenge the sequence goes by round instead, so that all players
+    /// get the same dice.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Rolls the dice from `seed` from the next turn on.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    /// Rolls the dice from a seed the player picked, from the next turn on.
+    /// Such a game is not ranked, as its dice can be known in advance.
+    pub fn choose_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.seed_chosen = true;
+    }
+
+    /// Whether the seed was picked by the player.
+    pub fn is_seed_chosen(&self) -> bool {
+        self.seed_chosen
+    }
+
+    /// The date of the daily challenge this game is played for, if any.
+    pub fn daily(&self) -> Option<NaiveDate> {
+        self.daily
+    }
+
+    /// Makes this the daily challenge of `date`, with the seed of the day.
+    pub fn set_daily(&mut self, date: NaiveDate) {
+        self.daily = Some(date);
+        self.seed = Daily::seed(date);
+    }
+
+    /// How many actions can be undone, 0 if undo is off.
+    pub fn undo_limit(&self) -> usize {
+        self.undo_limit
+    }
+
+    /// Lets the last `limit` keep toggles, placements and strikes since
+    /// the last roll be undone. A game with undo on is not ranked.
+    pub fn set_undo_limit(&mut self, limit: usize) {
+        self.undo_limit = limit;
+        while self.undo.len() > limit {
+            self.undo.pop_front();
+        }
+    }
+
+    /// Whether the result may go on a high-score table, which it may not
+    /// if moves could be taken back or the seed was picked.
+    pub fn is_ranked(&self) -> bool {
+        self.undo_limit == 0 && !self.seed_chosen
+    }
+
+    /// Takes back the last keep toggle, placement or strike. Rolls cannot
+    /// be undone, so neither can anything before the last one.
+    pub fn undo(&mut self) -> Result<(), GameError> {
+        let snapshot = self.undo.pop_back().ok_or(GameError::NothingToUndo)?;
+        self.dice.current = snapshot.dice;
+        self.dice.to_keep = snapshot.to_keep;
+        self.players = snapshot.players;
+        self.current = snapshot.current;
+        self.rolls = snapshot.rolls;
+        self.turns = snapshot.turns;
+        self.history.truncate(snapshot.history);
+        Ok(())
+    }
+
+    fn remember(&mut self) {
+        if self.undo_limit == 0 {
+            return;
+        }
+        if self.undo.len() == self.undo_limit {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(Snapshot {
+            dice: self.dice.current.clone(),
+            to_keep: self.dice.to_keep.clone(),
+            players: self.players.clone(),
+            current: self.current,
+            rolls: self.rolls,
+            turns: self.turns,
+            history: self.history.len(),
+        });
+    }
+
+    /// Every move made so far, oldest first.
+    pub fn history(&self) -> &[Move] {
+        &self.history
+    }
+
+    pub fn rolls_left(&self) -> u8 {
+        self.ruleset.rerolls() + 1 + self.saved_rolls() - self.rolls
+    }
+
+    /// Rolls the current player carried over from earlier turns, for
+    /// rulesets that save them.
+    pub fn saved_rolls(&self) -> u8 {
+        self.players[self.current].saved_rolls
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.players.iter().all(|player| player.score.is_final())
+    }
+
+    /// The players with the highest total, more than one on a tie.
+    pub fn winners(&self) -> Vec<&Player> {
+        let best = self.players.iter().map(|player| player.score.total()).max();
+        self.players.iter()
+            .filter(|player| Some(player.score.total()) == best)
+            .collect()
+    }
+
+    /// Rolls every die that is not kept and returns the result.
+    pub fn roll(&mut self) -> Result<&[usize], GameError> {
+        if self.is_over() {
+            return Err(GameError::GameOver);
+        }
+        if self.rolls_left() == 0 {
+            return Err(GameError::NoRollsLeft);
+        }
+        let kept = self.dice.to_keep.iter().map(|&die| die != 0).collect();
+        self.undo.clear();
+        self.roll_dice();
+        self.history.push(Move::Roll { kept, dice: self.dice.current.clone() });
+        Ok(&self.dice.current)
+    }
+
+    /// Makes a move from the history of another game with the same
+    /// players, rolling the dice to the recorded values.
+    pub fn apply(&mut self, step: &Move) -> Result<(), GameError> {
+        match *step {
+            Move::Roll { ref kept, ref dice } => {
+                if self.is_over() {
+                    return Err(GameError::GameOver);
+                }
+                if self.rolls_left() == 0 {
+                    return Err(GameError::NoRollsLeft);
+                }
+                if kept.len() != self.dice.current.len() || dice.len() != kept.len() {
+                    return Err(GameError::Invalid);
+                }
+                for i in 0..dice.len() {
+                    if kept[i] && (self.rolls == 0 || dice[i] != self.dice.current[i]) {
+                        return Err(GameError::Invalid);
+                    }
+                    self.dice.to_keep[i] = if kept[i] { dice[i] } else { 0 };
+                }
+                // Roll anyway to keep the dice in step with the seed.
+                self.undo.clear();
+                self.roll_dice();
+                self.dice.current = dice.clone();
+                self.history.push(step.clone());
+                Ok(())
+            },
+            Move::Place(category) => self.place(category).map(|_| ()),
+            Move::Strike(category) => self.strike(category),
+        }
+    }
+
+    /// The game as it was after its first `moves` moves.
+    pub fn replayed(&self, moves: usize) -> GameState {
+        let mut game = self.rematch();
+        game.seed = self.seed;
+        game.seed_chosen = self.seed_chosen;
+        game.daily = self.daily;
+        for step in &self.history[..moves] {
+            // The moves were made from the same start, so they are legal again.
+            game.apply(step).expect("the history does not replay");
+        }
+        game
+    }
+
+    /// Toggles whether die `i` is kept on the next roll and returns the
+    /// new state.
+    pub fn toggle_keep(&mut self, i: usize) -> Result<bool, GameError> {
+        if self.rolls == 0 {
+            return Err(GameError::NotRolled);
+        }
+        if i >= self.dice.current.len() {
+            return Err(GameError::NoSuchDie(i));
+        }
+        self.remember();
+        if self.dice.to_keep[i] == 0 {
+            self.dice.to_keep[i] = self.dice.current[i];
+        } else {
+            self.dice.to_keep[i] = 0;
+        }
+        Ok(self.dice.to_keep[i] != 0)
+    }
+
+    /// Random numbers for the choices of a computer player this turn. They
+    /// come from the seed of the game like the dice, but apart from them,
+    /// so the same seed gives the same game with the same bots.
+    pub fn turn_rng(&self) -> StdRng {
+        StdRng::seed_from_u64(!self.turn_seed())
+    }
+
+    /// The chance of filling each open slot of the current player this
+    /// turn, rolling the dice that are not kept; see `odds::chances`. With
+    /// none kept after a roll, the chances start from the dice as they are
+    /// and keep the best of them; see `odds::chances_from`.
+    pub fn chances(&self) -> Vec<(Category, f64)> {
+        let held: Vec<usize> = self.dice.to_keep.iter().cloned().filter(|&die| die != 0).collect();
+        if self.rolls > 0 && (held.is_empty() || self.rolls_left() == 0) {
+            odds::chances_from(self.ruleset(), self.score(), &self.dice.current, self.rolls_left())
+        } else {
+            odds::chances(self.ruleset(), self.score(), &held, self.rolls_left())
+        }
+    }
+
+    /// Returns the points the current dice would score in `category`,
+    /// or `None` if they do not qualify.
+    pub fn points(&self, category: Category) -> Option<u16> {
+        if self.rolls == 0 || self.score().get(category).is_none() {
+            return None;
+        }
+        self.ruleset.points(self.score(), category, &self.dice.current)
+    }
+
+    /// Scores the current dice in `category` and ends the turn.
+    pub fn place(&mut self, category: Category) -> Result<u16, GameError> {
+        self.check_slot(category)?;
+        match self.points(category) {
+            Some(points) => {
+                self.remember();
+                self.add_yatzy_bonus();
+                self.players[self.current].score.set(category, SlotState::Scored(points));
+                self.history.push(Move::Place(category));
+                self.end_turn();
+                Ok(points)
+            },
+            None => Err(GameError::Invalid),
+        }
+    }
+
+    /// Strikes out `category` and ends the turn.
+    pub fn strike(&mut self, category: Category) -> Result<(), GameError> {
+        self.check_slot(category)?;
+        self.remember();
+        self.add_yatzy_bonus();
+        self.players[self.current].score.set(category, SlotState::Struck);
+        self.history.push(Move::Strike(category));
+        self.end_turn();
+        Ok(())
+    }
+
+    /// Whether the current dice may be scored or struck out in `category`
+    /// now, taking forced play and the rules of the ruleset into account.
+    pub fn may_use(&self, category: Category) -> bool {
+        self.check_slot(category).is_ok()
+    }
+
+    fn check_slot(&self, category: Category) -> Result<(), GameError> {
+        if self.is_over() {
+            return Err(GameError::GameOver);
+        }
+        if self.rolls == 0 {
+            return Err(GameError::NotRolled);
+        }
+        match self.score().get(category) {
+            None => Err(GameError::NoSuchCategory(category)),
+            Some(SlotState::Empty) if self.forced => {
+                if self.next_slot() == Some(category) {
+                    Ok(())
+                } else {
+                    Err(GameError::NotAllowed)
+                }
+            },
+            Some(SlotState::Empty) => {
+                if self.ruleset.allowed(self.score(), category, &self.dice.current) {
+                    Ok(())
+                } else {
+                    Err(GameError::NotAllowed)
+                }
+            },
+            Some(_) => Err(GameError::AlreadySelected),
+        }
+    }
+
+    /// Awards the ruleset's bonus if the dice are another yatzy after one
+    /// has been scored.
+    fn add_yatzy_bonus(&mut self) {
+        if ScoreValidator::yatzy(&self.dice.current).is_some() &&
+            self.score().get(Category::Yatzy).map_or(0, SlotState::points) > 0 {
+                self.players[self.current].score.yatzy_bonus += self.ruleset.yatzy_bonus();
+            }
+    }
+
+    /// Rolls the dice that are not kept, starting the sequence of rolls of
+    /// the turn, or of the round in the daily challenge, on the first roll
+    /// of a turn.
+    fn roll_dice(&mut self) {
+        if self.rolls == 0 {
+            self.dice.rng = Box::new(StdRng::seed_from_u64(self.turn_seed()));
+        }
+        self.dice.roll();
+        self.rolls += 1;
+    }
+
+    /// The seed of the rolls of this turn, drawn from the seed of the game.
+    fn turn_seed(&self) -> u64 {
+        let round = match self.daily {
+            Some(_) => self.turns / self.players.len(),
+            None => self.turns,
+        };
+        let mut rounds = StdRng::seed_from_u64(self.seed);
+        for _ in 0..round {
+            rounds.next_u64();
+        }
+        rounds.next_u64()
+    }
+
+    fn end_turn(&mut self) {
+        if self.ruleset.saves_rolls() {
+            self.players[self.current].saved_rolls = self.rolls_left();
+        }
+        self.current = (self.current + 1) % self.players.len();
+        self.turns += 1;
+        self.rolls = 0;
+        for die in self.dice.to_keep.iter_mut() {
+            *die = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// An empty directory of its own for a test.
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rusty-yacht-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Rolls `dice` in `game`, keeping none of the dice from before.
+    fn roll(game: &mut GameState, dice: &[usize]) {
+        game.apply(&Move::Roll { kept: vec![false; dice.len()], dice: dice.to_vec() }).unwrap();
+    }
+
+    #[test]
+    fn validators_score_the_readme_examples() {
+        assert_eq!(ScoreValidator::two_pairs(&[1, 5, 5, 6, 6]), Some(22));
+        assert_eq!(ScoreValidator::two_pairs(&[5, 5, 5, 5, 5]), None);
+        let full_house = [2, 2, 5, 5, 5];
+        assert_eq!(ScoreValidator::full_house(&full_house), Some(19));
+        assert_eq!(ScoreValidator::chance(&full_house), Some(19));
+        assert_eq!(ScoreValidator::three_kind(&full_house), Some(15));
+        assert_eq!(ScoreValidator::two_pairs(&full_house), Some(14));
+        assert_eq!(ScoreValidator::one_pair(&full_house), Some(10));
+    }
+
+    #[test]
+    fn validators_score_each_combination() {
+        assert_eq!(ScoreValidator::ones(&[1, 1, 3, 4, 1]), Some(3));
+        assert_eq!(ScoreValidator::sixes(&[1, 2, 3, 4, 5]), None);
+        assert_eq!(ScoreValidator::four_kind(&[4, 4, 4, 4, 2]), Some(16));
+        assert_eq!(ScoreValidator::small_str(&[5, 3, 1, 2, 4]), Some(15));
+        assert_eq!(ScoreValidator::small_str(&[2, 3, 4, 5, 6]), None);
+        assert_eq!(ScoreValidator::large_str(&[2, 3, 4, 5, 6]), Some(20));
+        assert_eq!(ScoreValidator::yatzy(&[3, 3, 3, 3, 3]), Some(50));
+        assert_eq!(ScoreValidator::yatzy(&[3, 3, 3, 3, 2]), None);
+    }
+
+    #[test]
+    fn five_of_a_kind_is_a_full_house_in_yatzy_only() {
+        assert_eq!(ScoreValidator::full_house(&[5, 5, 5, 5, 5]), Some(25));
+        assert_eq!(ScoreValidator::full_house(&[4, 4, 4, 4, 1]), None);
+        assert_eq!(ScoreValidator::full_house_fixed(&[5, 5, 5, 5, 5]), None);
+    }
+
+    #[test]
+    fn maxi_yatzy_scores_six_dice() {
+        assert_eq!(ScoreValidator::three_pairs(&[1, 1, 4, 4, 6, 6]), Some(22));
+        assert_eq!(ScoreValidator::castle(&[2, 2, 2, 5, 5, 5]), Some(21));
+        assert_eq!(ScoreValidator::castle(&[2, 2, 2, 2, 2, 2]), None);
+        assert_eq!(ScoreValidator::tower(&[3, 3, 3, 3, 6, 6]), Some(24));
+        assert_eq!(ScoreValidator::full_str(&[6, 5, 4, 3, 2, 1]), Some(21));
+        assert_eq!(ScoreValidator::maxi_yatzy(&[4, 4, 4, 4, 4, 4]), Some(100));
+    }
+
+    #[test]
+    fn maxi_yatzy_saves_unused_rolls() {
+        let mut game = GameState::with_ruleset(Box::new(MaxiYatzy));
+        assert_eq!(game.rolls_left(), 3);
+        roll(&mut game, &[1, 2, 3, 4, 5, 6]);
+        game.place(Category::FullStraight).unwrap();
+        assert_eq!(game.saved_rolls(), 2);
+        assert_eq!(game.rolls_left(), 5);
+        for _ in 0..5 {
+            roll(&mut game, &[6, 6, 6, 6, 6, 6]);
+        }
+        assert_eq!(game.roll().err(), Some(GameError::NoRollsLeft));
+        game.place(Category::Yatzy).unwrap();
+        assert_eq!(game.saved_rolls(), 0);
+    }
+
+    #[test]
+    fn forced_play_takes_the_next_slot() {
+        let mut game = GameState::new();
+        game.set_forced(true);
+        roll(&mut game, &[6, 6, 6, 6, 6]);
+        assert_eq!(game.next_slot(), Some(Category::Ones));
+        assert_eq!(game.place(Category::Yatzy), Err(GameError::NotAllowed));
+        assert_eq!(game.strike(Category::Twos), Err(GameError::NotAllowed));
+        assert_eq!(game.place(Category::Ones), Err(GameError::Invalid));
+        game.strike(Category::Ones).unwrap();
+        assert_eq!(game.next_slot(), Some(Category::Twos));
+    }
+
+    #[test]
+    fn yahtzee_scores_fixed_points() {
+        assert_eq!(ScoreValidator::small_str_any(&[6, 3, 4, 5, 3]), Some(30));
+        assert_eq!(ScoreValidator::large_str_any(&[1, 2, 3, 4, 6]), None);
+        assert_eq!(ScoreValidator::full_house_fixed(&[2, 2, 5, 5, 5]), Some(25));
+        assert_eq!(ScoreValidator::three_kind_sum(&[4, 4, 4, 1, 2]), Some(15));
+    }
+
+    #[test]
+    fn yahtzee_joker_goes_by_the_rules_and_earns_the_bonus() {
+        let mut game = GameState::with_ruleset(Box::new(Yahtzee));
+        roll(&mut game, &[3, 3, 3, 3, 3]);
+        assert_eq!(game.place(Category::Yatzy), Ok(50));
+        roll(&mut game, &[3, 3, 1, 2, 4]);
+        game.place(Category::Threes).unwrap();
+
+        // The matching upper box is open, so the Joker has to go there.
+        roll(&mut game, &[5, 5, 5, 5, 5]);
+        assert!(!game.may_use(Category::Chance));
+        assert_eq!(game.place(Category::Fives), Ok(25));
+        assert_eq!(game.score().yatzy_bonus(), 100);
+
+        // With Threes filled in, any lower box takes it at full value, but
+        // no other upper box while a lower one is open.
+        roll(&mut game, &[3, 3, 3, 3, 3]);
+        assert_eq!(game.place(Category::Ones), Err(GameError::NotAllowed));
+        assert_eq!(game.place(Category::LargeStraight), Ok(40));
+        assert_eq!(game.score().yatzy_bonus(), 200);
+    }
+
+    #[test]
+    fn yahtzee_scored_as_zero_earns_no_bonus() {
+        let mut game = GameState::with_ruleset(Box::new(Yahtzee));
+        roll(&mut game, &[1, 2, 3, 4, 6]);
+        game.strike(Category::Yatzy).unwrap();
+        roll(&mut game, &[4, 4, 4, 4, 4]);
+        assert_eq!(game.place(Category::Fours), Ok(20));
+        assert_eq!(game.score().yatzy_bonus(), 0);
+    }
+
+    #[test]
+    fn undo_takes_back_a_placement() {
+        let mut game = GameState::new();
+        game.set_undo_limit(3);
+        assert!(!game.is_ranked());
+        roll(&mut game, &[2, 2, 5, 5, 5]);
+        game.toggle_keep(0).unwrap();
+        game.place(Category::FullHouse).unwrap();
+        assert_eq!(game.rolls(), 0);
+
+        game.undo().unwrap();
+        assert_eq!(game.score().get(Category::FullHouse), Some(SlotState::Empty));
+        assert_eq!(game.rolls(), 1);
+        assert_eq!(game.dice().current, vec![2, 2, 5, 5, 5]);
+        assert_eq!(game.history().len(), 1);
+        game.undo().unwrap();
+        assert_eq!(game.dice().to_keep, vec![0; 5]);
+
+        // Nothing before the roll can be taken back.
+        assert_eq!(game.undo(), Err(GameError::NothingToUndo));
+    }
+
+    #[test]
+    fn chances_start_from_the_roll() {
+        let mut game = GameState::new();
+        roll(&mut game, &[2, 2, 2, 2, 2]);
+        let yatzy = |game: &GameState| game.chances().into_iter()
+            .find(|&(category, _)| category == Category::Yatzy)
+            .unwrap().1;
+        assert_eq!(yatzy(&game), 1.0);
+        game.toggle_keep(0).unwrap();
+        assert!(yatzy(&game) < 0.5);
+    }
+
+    #[test]
+    fn undo_is_off_by_default() {
+        let mut game = GameState::new();
+        assert!(game.is_ranked());
+        roll(&mut game, &[1, 2, 3, 4, 5]);
+        game.place(Category::SmallStraight).unwrap();
+        assert_eq!(game.undo(), Err(GameError::NothingToUndo));
+    }
+
+    #[test]
+    fn replay_steps_through_the_history() {
+        let seats = vec![("Albin".to_string(), None), ("Greedy".to_string(), Some(Strategy::Greedy))];
+        let mut game = GameState::with_seats(Box::new(Yatzy), seats);
+        game.set_seed(7);
+        while !game.is_over() {
+            Strategy::Greedy.play_turn(&mut game);
+        }
+        let moves = game.history().len();
+        for shown in 0..=moves {
+            let step = game.replayed(shown);
+            assert_eq!(step.history(), &game.history()[..shown]);
+        }
+        let end = game.replayed(moves);
+        assert!(end.is_over());
+        for (replayed, played) in end.players().iter().zip(game.players()) {
+            assert_eq!(replayed.score().total(), played.score().total());
+        }
+        // Half way through a turn the dice are as they were rolled.
+        let first_roll = game.replayed(1);
+        assert_eq!(first_roll.rolls(), 1);
+        assert_eq!(Some(&Move::Roll { kept: vec![false; 5], dice: first_roll.dice().current.clone() }),
+                   game.history().first());
+    }
+
+    /// The first roll of each of the next `turns` turns in `game`.
+    fn first_rolls(game: &mut GameState, turns: usize) -> Vec<Vec<usize>> {
+        (0..turns)
+            .map(|_| {
+                let dice = game.roll().unwrap().to_vec();
+                let category = game.next_slot().unwrap();
+                game.strike(category).unwrap();
+                dice
+            })
+            .collect()
+    }
+
+    #[test]
+    fn daily_dice_are_the_same_for_every_seat_and_ruleset() {
+        let date = NaiveDate::from_ymd_opt(2019, 5, 1).unwrap();
+        let seats = vec!["Albin".to_string(), "Kim".to_string()];
+        let mut daily = GameState::with_players(Box::new(Yatzy), seats.clone());
+        daily.set_daily(date);
+        let rolls = first_rolls(&mut daily, 6);
+        for round in rolls.chunks(2) {
+            assert_eq!(round[0], round[1]);
+        }
+
+        let mut yahtzee = GameState::with_ruleset(Box::new(Yahtzee));
+        yahtzee.set_daily(date);
+        let solitaire: Vec<Vec<usize>> = rolls.iter().step_by(2).cloned().collect();
+        assert_eq!(first_rolls(&mut yahtzee, 3), solitaire);
+
+        let mut other_day = GameState::with_ruleset(Box::new(Yatzy));
+        other_day.set_daily(date.succ_opt().unwrap());
+        assert_ne!(first_rolls(&mut other_day, 3), solitaire);
+
+        // Outside the daily challenge each seat gets dice of its own.
+        let mut game = GameState::with_players(Box::new(Yatzy), seats);
+        game.set_seed(Daily::seed(date));
+        let rolls = first_rolls(&mut game, 6);
+        assert!(rolls.chunks(2).any(|round| round[0] != round[1]));
+    }
+
+    #[test]
+    fn daily_counts_the_first_game_of_each_name() {
+        let dir = scratch_dir("daily");
+        let path = dir.join("daily");
+        let date = NaiveDate::from_ymd_opt(2019, 5, 1).unwrap();
+        assert!(Daily::log(&path, date, "yatzy", "Albin", 180).unwrap());
+        assert!(!Daily::log(&path, date, "yatzy", "albin", 250).unwrap());
+        assert!(Daily::log(&path, date, "yatzy", "Kim", 200).unwrap());
+        assert!(Daily::log(&path, date, "yahtzee", "Albin", 210).unwrap());
+        assert!(Daily::log(&path, date.succ_opt().unwrap(), "yatzy", "Albin", 150).unwrap());
+        assert_eq!(Daily::results(&path, date, "yatzy").unwrap(),
+                   vec![(200, "Kim".to_string()), (180, "Albin".to_string())]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    const V1_FILE: &str = "Albin                   | 2019-05-01 |   250\n\
+                           not a result\n\
+                           Kim                     | 2019-05-02 |   180\n";
+
+    #[test]
+    fn highscore_v1_is_read_as_it_is() {
+        let dir = scratch_dir("read");
+        let path = dir.join("highscore");
+        fs::write(&path, V1_FILE).unwrap();
+
+        let (highscore, skipped) = Highscore::new(&path).unwrap();
+        let results: Vec<(&str, u32)> = highscore.iter().map(|entry| (&*entry.name, entry.score)).collect();
+        assert_eq!(results, vec![("Albin", 250), ("Kim", 180)]);
+        assert_eq!(skipped, 1);
+        // The first version only had yatzy, so that is where they show.
+        assert!(highscore.iter().all(|entry| entry.in_ruleset(&Yatzy.id())));
+        // Reading leaves the file alone.
+        assert_eq!(fs::read_to_string(&path).unwrap(), V1_FILE);
+        assert!(!dir.join("highscore.v1").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn highscore_v1_is_migrated_on_log() {
+        let dir = scratch_dir("migrate");
+        let path = dir.join("highscore");
+        fs::write(&path, V1_FILE).unwrap();
+
+        let migration = Highscore::log(&path, &Entry::new("Lena", "yahtzee", &Score::new())).unwrap().unwrap();
+        assert_eq!(migration.backup, dir.join("highscore.v1"));
+        assert_eq!(migration.skipped, 1);
+        assert_eq!(fs::read_to_string(dir.join("highscore.v1")).unwrap(), V1_FILE);
+        assert!(fs::read_to_string(&path).unwrap().starts_with(HIGHSCORE_HEADER));
+
+        let (highscore, skipped) = Highscore::new(&path).unwrap();
+        assert_eq!(skipped, 0);
+        let names: Vec<&str> = highscore.iter().map(|entry| &*entry.name).collect();
+        assert_eq!(names, vec!["Albin", "Kim", "Lena"]);
+        assert_eq!(highscore.iter().filter(|entry| entry.in_ruleset(&Yatzy.id())).count(), 2);
+        // Converted once; the next result is just added.
+        assert!(Highscore::log(&path, &Entry::new("Lena", "yatzy", &Score::new())).unwrap().is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn highscore_migration_keeps_earlier_backup() {
+        let dir = scratch_dir("backup");
+        let path = dir.join("highscore");
+        fs::write(dir.join("highscore.v1"), "Albin                   | 2019-05-01 |   250\n").unwrap();
+        fs::write(&path, "Kim                     | 2019-05-02 |   180\n").unwrap();
+
+        let migration = Highscore::log(&path, &Entry::new("Lena", "yatzy", &Score::new())).unwrap().unwrap();
+        assert_eq!(migration.backup, dir.join("highscore.v1.1"));
+        assert!(fs::read_to_string(dir.join("highscore.v1")).unwrap().contains("Albin"));
+        assert!(fs::read_to_string(dir.join("highscore.v1.1")).unwrap().contains("Kim"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn empty_highscore_is_not_migrated() {
+        let dir = scratch_dir("empty");
+        let path = dir.join("highscore");
+        fs::write(dir.join("highscore.v1"), "Albin                   | 2019-05-01 |   250\n").unwrap();
+        fs::write(&path, "\n").unwrap();
+
+        assert!(Highscore::new(&path).unwrap().0.is_empty());
+        assert!(Highscore::log(&path, &Entry::new("Lena", "yatzy", &Score::new())).unwrap().is_none());
+        assert_eq!(Highscore::new(&path).unwrap().0.len(), 1);
+        assert!(fs::read_to_string(dir.join("highscore.v1")).unwrap().contains("Albin"));
+        assert!(!dir.join("highscore.v1.1").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
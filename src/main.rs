@@ -19,65 +19,662 @@
 //                                                                      //
 //////////////////////////////////////////////////////////////////////////
 
-extern crate dirs;
+extern crate chrono;
 extern crate rusty_yacht;
+extern crate termion;
 
-use rusty_yacht::Dice;
-use rusty_yacht::Score;
-use rusty_yacht::ScoreValidator;
+use std::cmp::Reverse;
+use std::env;
+use std::io;
+use std::path::PathBuf;
+use std::process;
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+use rusty_yacht::Daily;
+use rusty_yacht::Entry;
+use rusty_yacht::Error;
+use rusty_yacht::GameState;
 use rusty_yacht::Highscore;
-use rusty_yacht::welcome;
-use rusty_yacht::clear_screen;
+use rusty_yacht::Ruleset;
+use rusty_yacht::bot::Strategy;
+use rusty_yacht::config::{self, Config};
+use rusty_yacht::save;
+use rusty_yacht::terminal;
+use rusty_yacht::theme;
+use rusty_yacht::ui;
+use rusty_yacht::ui::clear_screen;
+
+/// What to do, named by the first argument.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Play,
+    Scores,
+    Simulate,
+    Replay,
+    Config,
+}
+
+const USAGE: &str = "\
+Usage: rusty-yacht [COMMAND] [OPTIONS]
+
+Commands:
+  play                     Play a game; this is the default
+  scores                   Show the high-score table
+  simulate                 Let computer players play many games each
+  replay FILE              Step through a recorded game
+  config                   Show where the config file is and check it
+
+Options for play:
+  --ruleset NAME           yatzy, yahtzee or maxi-yatzy
+  --players N              Number of human players, 1 by default
+  --bot STRATEGY           Add a computer player: random, greedy,
+                           heuristic or optimal
+  --bots STRATEGY,...      Add several computer players
+  --forced                 Play forced yatzy
+  --seed N                 Roll the dice from seed N, unranked
+  --daily                  Play the daily challenge
+  --undo N                 Allow taking back the last N actions
+  --highscore-file FILE    Keep the high-score table in FILE
+
+Options for scores:
+  --ruleset NAME           Only results in this ruleset
+  --name NAME              Only results of this player
+  --daily                  Today's daily challenge instead, in the
+                           configured ruleset unless --ruleset is given
+  --highscore-file FILE    Read the high-score table from FILE
+
+Options for simulate:
+  --bot STRATEGY, --bots STRATEGY,...
+                           The bots to compare, all of them by default
+  --games N                Games for each bot, 100 by default
+  --ruleset NAME, --forced, --seed N
+                           As for play
+
+Options for config:
+  --print-default          Print a config file with every setting at
+                           its default
+
+Options for all commands:
+  --config FILE            Read the settings from FILE
+  -h, --help               Show this help
+  -V, --version            Show the version
+";
 
 fn main() {
-    let mut dice = Dice::new();
-    let validator = ScoreValidator::new();
-    let mut score = Score::new();
-    let path = Highscore::new_path();
+    if env::args().any(|arg| arg == "--help" || arg == "-h") {
+        print!("{}", USAGE);
+        return;
+    }
+    if env::args().any(|arg| arg == "--version" || arg == "-V") {
+        println!("rusty-yacht {}", env!("CARGO_PKG_VERSION"));
+        return;
+    }
+    let command = parse_command();
+    let rest = check_args(command);
+    match (command, rest.len()) {
+        (Command::Replay, 1) | (_, 0) => (),
+        (Command::Replay, _) => {
+            eprintln!("Usage: rusty-yacht replay FILE");
+            process::exit(1);
+        },
+        _ => {
+            eprintln!("Unexpected argument '{}'. See rusty-yacht --help.", rest[0]);
+            process::exit(1);
+        },
+    }
+    if command == Command::Config {
+        return show_config();
+    }
+    match config_path().and_then(|path| Config::load(&path)) {
+        Ok(mut settings) => {
+            if !theme::supported() {
+                settings.theme = None;
+            }
+            config::set(settings);
+        },
+        Err(e) => {
+            eprintln!("Rusty Yacht can't start: {}", e);
+            process::exit(1);
+        },
+    }
+    let result = match command {
+        Command::Config => unreachable!(),
+        Command::Scores => scores(),
+        Command::Simulate => simulate(),
+        Command::Play => in_terminal(play),
+        Command::Replay => in_terminal(|| replay(&PathBuf::from(&rest[0]))),
+    };
+    if let Err(e) = result {
+        eprintln!("Rusty Yacht stopped: {}", e);
+        process::exit(1);
+    }
+}
 
-    clear_screen();
-    println!("  Press Enter to roll the dice\n  or Ctrl+c at any time to exit.");
-    score.print();
-    welcome();
+/// Runs a command that takes over the terminal, restoring it after.
+fn in_terminal<F: FnOnce() -> Result<(), Error>>(command: F) -> Result<(), Error> {
+    if !termion::is_tty(&io::stdin()) || !termion::is_tty(&io::stdout()) {
+        eprintln!("Rusty Yacht needs a terminal to play in.");
+        process::exit(1);
+    }
+    let _terminal = match terminal::Guard::new() {
+        Ok(guard) => guard,
+        Err(e) => {
+            eprintln!("Couldn't read the terminal settings: {}", e);
+            process::exit(1);
+        },
+    };
+    command()
+}
+
+fn play() -> Result<(), Error> {
+    let ruleset = parse_ruleset();
+    let humans = parse_players();
+    let bots = parse_bots();
+    if humans + bots.len() == 0 {
+        eprintln!("A game needs at least one player");
+        process::exit(1);
+    }
+    let forced = env::args().any(|arg| arg == "--forced");
+    let daily = env::args().any(|arg| arg == "--daily");
+    let seed = parse_seed();
+    let undo = parse_undo();
+    if daily && undo > 0 {
+        eprintln!("The daily challenge is ranked, so undo is off.");
+    }
+    if daily && seed.is_some() {
+        eprintln!("The daily challenge has its own seed; leave out --seed");
+        process::exit(1);
+    }
+
+    let mut game = match resume()? {
+        Some(game) => game,
+        None => {
+            let mut game = new_game(ruleset, humans, &bots)?;
+            game.set_forced(forced);
+            if daily {
+                game.set_daily(Daily::today());
+            }
+            if let Some(seed) = seed {
+                game.choose_seed(seed);
+            }
+            if !daily {
+                game.set_undo_limit(undo);
+            }
+            game
+        },
+    };
+    if game.players().iter().any(|player| player.strategy() == Some(Strategy::Optimal)) {
+        ui::hint_solver(&game);
+    }
 
-    let mut count = 0;
+    ui::welcome(&game)?;
+
+    // A game resumed in the middle of a turn goes on with the dice it had.
+    let mut rolled = game.rolls() > 0;
     loop {
+        if game.players()[game.current_player()].strategy().is_some() {
+            ui::play_bot_turn(&mut game)?;
+            if game.is_over() {
+                return game_over(&game);
+            }
+            autosave(&game);
+            continue;
+        }
+
         clear_screen();
+        ui::print_turn(&game);
         println!("  Press Enter to roll the dice\n  or Ctrl+c at any time to exit.");
-        score.print();
-
-        if count < 2 && !dice.keep_all() {
-            // Continue to roll
-            clear_screen();
-            score.print();
-            dice.roll();
-            count += 1;
-            dice.print();
-            dice.select(&mut score, &count);           
-        } else {
-            dice.roll();
-            count = 0;
-            clear_screen();
-            // Time to place points
-            println!("  Where do you want to place your points?");
-            println!("  Use the arrow keys and press Enter to select.");
-            score.place_points(validator, &dice);
-
-            if score.is_final() {
+        ui::print_game(&game, None);
+
+        if game.rolls_left() > 0 && !game.dice().keep_all() {
+            if !rolled {
+                game.roll().unwrap();
+            }
+            rolled = false;
+            if game.rolls_left() > 0 {
+                // Continue to roll
                 clear_screen();
-                println!("  GAME OVER");
-                score.print();
-                dice.print();
-                score.log(&path);
-                break;
+                ui::print_game(&game, None);
+                game.dice().print();
+                ui::select_dice(&mut game)?;
+                continue;
+            }
+        }
+
+        clear_screen();
+        // Time to place points
+        ui::print_turn(&game);
+        println!("  Where do you want to place your points?");
+        ui::print_slot_keys(&game);
+        ui::place_points(&mut game)?;
+
+        if game.is_over() {
+            return game_over(&game);
+        }
+        autosave(&game);
+
+        clear_screen();
+        ui::print_game(&game, None);
+        game.dice().print();
+    }
+}
+
+/// Offers to resume the saved game, if there is one.
+fn resume() -> Result<Option<GameState>, Error> {
+    let path = match save::path() {
+        Ok(ref path) if path.exists() => path.clone(),
+        _ => return Ok(None),
+    };
+    if !ui::offer_resume()? {
+        return Ok(None);
+    }
+    match save::load(&path) {
+        Ok(game) => Ok(Some(game)),
+        Err(e) => {
+            eprintln!("Couldn't resume the saved game: {}", e);
+            Ok(None)
+        },
+    }
+}
+
+fn new_game(ruleset: Box<dyn Ruleset>, humans: usize, bots: &[Strategy]) -> Result<GameState, Error> {
+    if humans == 1 && bots.is_empty() {
+        return Ok(GameState::with_ruleset(ruleset));
+    }
+    let mut seats: Vec<(String, Option<Strategy>)> = (1..=humans)
+        .map(|i| ui::ask_name(&format!("Name of player {}:", i)).map(|name| (name, None)))
+        .collect::<Result<_, _>>()?;
+    for (i, &strategy) in bots.iter().enumerate() {
+        let same = bots[..i].iter().filter(|&&other| other == strategy).count();
+        let name = match same {
+            0 => strategy.name().to_string(),
+            _ => format!("{} {}", strategy.name(), same + 1),
+        };
+        seats.push((name, Some(strategy)));
+    }
+    Ok(GameState::with_seats(ruleset, seats))
+}
+
+fn autosave(game: &GameState) {
+    if let Err(e) = save::path().and_then(|path| save::store(&path, game)) {
+        eprintln!("Couldn't save the game: {}", e);
+    }
+}
+
+fn game_over(game: &GameState) -> Result<(), Error> {
+    if let Err(e) = save::path().and_then(|path| save::remove(&path)) {
+        eprintln!("Couldn't remove the saved game: {}", e);
+    }
+    clear_screen();
+    println!("  GAME OVER");
+    ui::print_game(game, None);
+    game.dice().print();
+    let solitaire = game.players().len() == 1;
+    if !solitaire {
+        announce_winners(game);
+    }
+    match save::record(game) {
+        Ok(replay) => println!("  Watch this game again with: rusty-yacht replay {}", replay.display()),
+        Err(e) => eprintln!("Couldn't record the game: {}", e),
+    }
+    if game.is_ranked() {
+        log_results(game)?;
+    } else {
+        let reason = if game.undo_limit() > 0 { "Undo was on" } else { "The seed was picked" };
+        println!("  {}, so the game does not go on the high-score table.", reason);
+    }
+    println!("  This game had seed {}. Play it again with --seed {}.", game.seed(), game.seed());
+    ui::offer_analysis(game)
+}
+
+/// Logs the result of each human player of `game` and shows the table it
+/// went on, the daily leaderboard for the daily challenge.
+fn log_results(game: &GameState) -> Result<(), Error> {
+    let solitaire = game.players().len() == 1;
+    let ruleset = game.ruleset().id();
+    let mut entries = Vec::new();
+    for player in game.players().iter().filter(|player| player.strategy().is_none()) {
+        let name = if solitaire {
+            match config::get().name {
+                Some(ref name) => name.clone(),
+                None => ui::ask_name("Input a name to log your score:")?,
+            }
+        } else {
+            player.name().to_string()
+        };
+        entries.push(Entry::new(&name, &ruleset, player.score()));
+    }
+    entries.sort_by_key(|entry| Reverse(entry.score));
+    if solitaire {
+        clear_screen();
+    }
+    if let Some(date) = game.daily() {
+        let results = log_daily(date, &ruleset, &entries).unwrap_or_else(|e| {
+            println!("  The daily leaderboard can't be used ({}),\n  so only this game is shown.", e);
+            entries.iter().map(|entry| (entry.score, entry.name.clone())).collect()
+        });
+        Daily::print(&results, date);
+    } else {
+        let highscore = log_highscore(&ruleset, &entries).unwrap_or_else(|e| {
+            println!("  The high-score table can't be used ({}),\n  so only this game is shown.", e);
+            entries.clone()
+        });
+        Highscore::print(&highscore);
+    }
+    Ok(())
+}
+
+/// Adds `entries` to the high-score table and returns the part of it in
+/// the ruleset with id `ruleset`.
+fn log_highscore(ruleset: &str, entries: &[Entry]) -> Result<Vec<Entry>, Error> {
+    let path = highscore_path()?;
+    for entry in entries {
+        if let Some(migration) = Highscore::log(&path, entry)? {
+            eprintln!("The high-score file was converted to a new format; the old one is in {}.",
+                      migration.backup.display());
+            if migration.skipped > 0 {
+                eprintln!("{} lines of it could not be read and were left out.", migration.skipped);
             }
+        }
+    }
+    Ok(read_highscore(&path)?.into_iter()
+        .filter(|entry| entry.in_ruleset(ruleset))
+        .collect())
+}
+
+/// Reads the high-score table at `path`, warning about lines that could
+/// not be read.
+fn read_highscore(path: &PathBuf) -> Result<Vec<Entry>, Error> {
+    let (highscore, skipped) = Highscore::new(path)?;
+    if skipped > 0 {
+        eprintln!("Skipped {} lines of {} that could not be read.", skipped, path.display());
+    }
+    Ok(highscore)
+}
+
+/// Adds `entries` to the daily leaderboard of `date` and returns all of it.
+fn log_daily(date: NaiveDate, ruleset: &str, entries: &[Entry]) -> Result<Vec<(u32, String)>, Error> {
+    let path = Daily::new_path()?;
+    for entry in entries {
+        if !Daily::log(&path, date, ruleset, &entry.name, entry.score)? {
+            println!("  {} has played today's challenge before; only the first game counts.", entry.name);
+        }
+    }
+    Daily::results(&path, date, ruleset)
+}
+
+/// Plays back the game recorded at `path`.
+fn replay(path: &PathBuf) -> Result<(), Error> {
+    match save::load(path) {
+        Ok(game) => ui::replay(&game),
+        Err(e) => {
+            eprintln!("Couldn't read the replay {}: {}", path.display(), e);
+            process::exit(1);
+        },
+    }
+}
+
+/// Prints the high-score table, or the daily leaderboard, without playing.
+fn scores() -> Result<(), Error> {
+    let ruleset = parse_ruleset_filter();
+    let name = value_of("--name");
+    if env::args().any(|arg| arg == "--daily") {
+        let id = ruleset.map_or(config::get().ruleset.clone(), |ruleset| ruleset.id());
+        let today = Daily::today();
+        let results: Vec<(u32, String)> = Daily::results(&Daily::new_path()?, today, &id)?.into_iter()
+            .filter(|(_, player)| name.as_ref().is_none_or(|name| player.eq_ignore_ascii_case(name)))
+            .collect();
+        Daily::print(&results, today);
+        return Ok(());
+    }
+    let highscore: Vec<Entry> = read_highscore(&highscore_path()?)?.into_iter()
+        .filter(|entry| ruleset.as_ref().is_none_or(|ruleset| entry.in_ruleset(&ruleset.id())))
+        .filter(|entry| name.as_ref().is_none_or(|name| entry.name.eq_ignore_ascii_case(name)))
+        .collect();
+    Highscore::print(&highscore);
+    Ok(())
+}
 
-            score.print();
-            dice.print();
-            dice.reroll_all();
-            clear_screen();
-            score.print();
-            dice.print();
+/// Lets each bot play `--games N` solitaire games and prints how they did.
+fn simulate() -> Result<(), Error> {
+    let ruleset = parse_ruleset();
+    let forced = env::args().any(|arg| arg == "--forced");
+    let games: usize = number_of("--games").unwrap_or(100);
+    let seed = parse_seed();
+    let mut bots = parse_bots();
+    if bots.is_empty() {
+        bots = Strategy::ALL.to_vec();
+    }
+    if games == 0 {
+        eprintln!("--games needs to be at least 1");
+        process::exit(1);
+    }
+
+    let name = ruleset.name();
+    println!("{} games of {} for each bot.\n", games, name);
+    println!("{:<12}{:>8}{:>8}{:>6}{:>6}{:>8}", "Bot", "Mean", "SD", "Min", "Max", "Bonus");
+    for strategy in bots {
+        let mut totals = Vec::with_capacity(games);
+        let mut bonuses = 0;
+        for i in 0..games {
+            let ruleset = rusty_yacht::ruleset_by_name(&ruleset.id()).unwrap();
+            let mut game = GameState::with_seats(ruleset, vec![(strategy.name().to_string(), Some(strategy))]);
+            game.set_forced(forced);
+            if let Some(seed) = seed {
+                game.set_seed(seed.wrapping_add(i as u64));
+            }
+            if i == 0 && strategy == Strategy::Optimal {
+                ui::hint_solver(&game);
+            }
+            while !game.is_over() {
+                strategy.play_turn(&mut game);
+            }
+            let score = game.players()[0].score();
+            totals.push(f64::from(score.total()));
+            if score.bonus().points() > 0 {
+                bonuses += 1;
+            }
         }
+        let mean = totals.iter().sum::<f64>() / games as f64;
+        let variance = totals.iter().map(|total| (total - mean).powi(2)).sum::<f64>() / games as f64;
+        let min = totals.iter().cloned().fold(f64::MAX, f64::min);
+        let max = totals.iter().cloned().fold(f64::MIN, f64::max);
+        println!("{:<12}{:>8.1}{:>8.1}{:>6}{:>6}{:>7.0}%", strategy.name(), mean, variance.sqrt(),
+                 min, max, 100.0 * bonuses as f64 / games as f64);
+    }
+    Ok(())
+}
+
+/// Prints the default config file with `--print-default`, and otherwise
+/// where the config file is and whether it can be read.
+fn show_config() {
+    if env::args().any(|arg| arg == "--print-default") {
+        print!("{}", config::DEFAULT);
+        return;
+    }
+    let path = match config_path() {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("Couldn't find the config file: {}", e);
+            process::exit(1);
+        },
+    };
+    if !path.exists() {
+        println!("There is no config file at {}, so the defaults are used.", path.display());
+        println!("Start one with: rusty-yacht config --print-default > {}", path.display());
+        return;
     }
+    match Config::load(&path) {
+        Ok(_) => println!("The config file at {} is fine.", path.display()),
+        Err(e) => {
+            eprintln!("Rusty Yacht can't start: {}", e);
+            process::exit(1);
+        },
+    }
+}
+
+fn announce_winners(game: &GameState) {
+    let winners: Vec<&str> = game.winners().iter().map(|player| player.name()).collect();
+    let total = game.winners()[0].score().total();
+    match winners.len() {
+        1 => println!("  {} wins with {} points!", winners[0], total),
+        _ => println!("  It's a tie between {} with {} points!", winners.join(" and "), total),
+    }
+}
+
+/// Reads the command from the first argument, defaulting to play.
+fn parse_command() -> Command {
+    match env::args().nth(1).as_deref() {
+        None => Command::Play,
+        Some(arg) if arg.starts_with('-') => Command::Play,
+        Some("play") => Command::Play,
+        Some("scores") => Command::Scores,
+        Some("simulate") => Command::Simulate,
+        Some("replay") => Command::Replay,
+        Some("config") => Command::Config,
+        Some(other) => {
+            eprintln!("Unknown command '{}'. See rusty-yacht --help.", other);
+            process::exit(1);
+        },
+    }
+}
+
+/// Exits with a message on an option that `command` does not take, and
+/// returns the arguments that are not options.
+fn check_args(command: Command) -> Vec<String> {
+    // Each option, and whether it is followed by a value.
+    let options: &[(&str, bool)] = match command {
+        Command::Play => &[("--ruleset", true), ("--players", true), ("--bot", true),
+                           ("--bots", true), ("--forced", false), ("--seed", true),
+                           ("--daily", false), ("--undo", true), ("--highscore-file", true),
+                           ("--config", true)],
+        Command::Scores => &[("--ruleset", true), ("--name", true), ("--daily", false),
+                             ("--highscore-file", true), ("--config", true)],
+        Command::Simulate => &[("--ruleset", true), ("--bot", true), ("--bots", true),
+                               ("--games", true), ("--forced", false), ("--seed", true),
+                               ("--config", true)],
+        Command::Replay => &[("--config", true)],
+        Command::Config => &[("--print-default", false), ("--config", true)],
+    };
+    let mut rest = Vec::new();
+    let mut args = env::args().skip(1).peekable();
+    if args.peek().is_some_and(|arg| !arg.starts_with('-')) {
+        args.next();
+    }
+    while let Some(arg) = args.next() {
+        if !arg.starts_with('-') {
+            rest.push(arg);
+            continue;
+        }
+        match options.iter().find(|&&(option, _)| option == arg) {
+            Some(&(_, true)) => if args.next().is_none() {
+                eprintln!("{} needs a value. See rusty-yacht --help.", arg);
+                process::exit(1);
+            },
+            Some(&(_, false)) => (),
+            None => {
+                eprintln!("Unknown option '{}'. See rusty-yacht --help.", arg);
+                process::exit(1);
+            },
+        }
+    }
+    rest
+}
+
+/// Reads the number of human players from `--players N`, defaulting to one.
+fn parse_players() -> usize {
+    number_of("--players").unwrap_or(1)
+}
+
+/// Reads the ruleset from `--ruleset NAME`, defaulting to the one in the
+/// config file.
+fn parse_ruleset() -> Box<dyn Ruleset> {
+    parse_ruleset_filter().unwrap_or_else(|| rusty_yacht::ruleset_by_name(&config::get().ruleset).unwrap())
+}
+
+/// Reads the ruleset from `--ruleset NAME`, if given.
+fn parse_ruleset_filter() -> Option<Box<dyn Ruleset>> {
+    let name = value_of("--ruleset")?;
+    Some(rusty_yacht::ruleset_by_name(&name).unwrap_or_else(|| {
+        let names: Vec<_> = rusty_yacht::rulesets().iter()
+            .map(|ruleset| ruleset.id())
+            .collect();
+        eprintln!("Unknown ruleset '{}'. Choose one of: {}", name, names.join(", "));
+        process::exit(1);
+    }))
+}
+
+/// Reads the seed of the dice from `--seed N`.
+fn parse_seed() -> Option<u64> {
+    number_of("--seed")
+}
+
+/// Reads how many actions can be undone from `--undo N`, defaulting to
+/// the config file.
+fn parse_undo() -> usize {
+    number_of("--undo").unwrap_or(config::get().undo)
+}
+
+/// Reads the computer players from every `--bot STRATEGY` and every
+/// `--bots STRATEGY,STRATEGY,...`, in order.
+fn parse_bots() -> Vec<Strategy> {
+    let mut bots = Vec::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg != "--bot" && arg != "--bots" {
+            continue;
+        }
+        let value = args.next().unwrap_or_default();
+        let names: Vec<&str> = if arg == "--bots" { value.split(',').collect() } else { vec![&value] };
+        for name in names {
+            match Strategy::by_name(name) {
+                Some(strategy) => bots.push(strategy),
+                None => {
+                    let names: Vec<_> = Strategy::ALL.iter()
+                        .map(|strategy| strategy.name().to_lowercase())
+                        .collect();
+                    eprintln!("Unknown bot '{}'. Choose one of: {}", name, names.join(", "));
+                    process::exit(1);
+                },
+            }
+        }
+    }
+    bots
+}
+
+/// Where the high-score table is kept, `--highscore-file FILE` or the
+/// usual place.
+fn highscore_path() -> Result<PathBuf, Error> {
+    match value_of("--highscore-file") {
+        Some(path) => Ok(PathBuf::from(path)),
+        None => Highscore::new_path(),
+    }
+}
+
+/// Where the settings are read from, `--config FILE` or the usual place.
+fn config_path() -> Result<PathBuf, Error> {
+    match value_of("--config") {
+        Some(path) => Ok(PathBuf::from(path)),
+        None => Config::path(),
+    }
+}
+
+/// The value given after `flag`, if the flag is there.
+fn value_of(flag: &str) -> Option<String> {
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == flag {
+            return Some(args.next().unwrap_or_default());
+        }
+    }
+    None
+}
+
+/// The number given after `flag`, if the flag is there.
+fn number_of<T: FromStr>(flag: &str) -> Option<T> {
+    value_of(flag).map(|value| value.parse().unwrap_or_else(|_| {
+        eprintln!("{} needs a number", flag);
+        process::exit(1);
+    }))
 }
@@ -0,0 +1,202 @@
+//////////////////////////////////////////////////////////////////////////
+//                                                                      //
+// Rusty Yacht - A yatzy game for the terminal               R U S T Y  //
+// Copyright (C) 2019  Albin Söderqvist <albin@fripost.org>  U       A  //
+//                                                           S       C  //
+// This game is free software: you can redistribute it       T       H  //
+// and/or modify it under the terms of GNU General Public    Y A C H T  //
+// License as published by the Free Software Foundation,                //
+// either version 3 of the License, or (at your option)                 //
+// any later version.                                                   //
+//                                                                      //
+// Rusty Yacht is distributed in the hope that it will be fun to play,  //
+// but WITHOUT ANY WARRANTY; without even the implied warranty of       //
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the         //
+// GNU General Public License for more details.                         //
+//                                                                      //
+// You should have received a copy of the GNU General Public License    //
+// along with the game. If not, see <https://www.gnu.org/licenses/>.    //
+//                                                                      //
+//////////////////////////////////////////////////////////////////////////
+
+//! The chance of filling each open slot before the turn is over. The dice
+//! not held are rolled, and every roll after that keeps whatever dice give
+//! the best chance for that one slot, so each slot is worked out on its
+//! own, exactly, over every way the rerolls can land. Once the dice are
+//! rolled, standing on them as they are counts as well.
+
+use std::collections::HashMap;
+
+use {outcomes, Category, Ruleset, Score};
+
+/// Every multiset of up to `dice` dice, and how they relate.
+struct Keeps {
+    dice: usize,
+    /// Sorted, fewest dice first.
+    keeps: Vec<Vec<usize>>,
+    index: HashMap<Vec<usize>, usize>,
+    /// The keep with one more die of each face, for keeps under `dice`.
+    added: Vec<[usize; 6]>,
+    /// The keeps with one die less.
+    removed: Vec<Vec<usize>>,
+}
+
+impl Keeps {
+    fn new(dice: usize) -> Keeps {
+        let mut keeps = Vec::new();
+        for count in 0..=dice {
+            keeps.extend(outcomes(count));
+        }
+        let index: HashMap<Vec<usize>, usize> = keeps.iter().cloned()
+            .enumerate()
+            .map(|(i, keep)| (keep, i))
+            .collect();
+        let added = keeps.iter().map(|keep| {
+            let mut added = [0; 6];
+            if keep.len() < dice {
+                for face in 1..7 {
+                    let mut more = keep.clone();
+                    more.push(face);
+                    more.sort();
+                    added[face - 1] = index[&more];
+                }
+            }
+            added
+        }).collect();
+        let removed = keeps.iter().map(|keep| {
+            let mut removed: Vec<usize> = (0..keep.len()).map(|i| {
+                let mut less = keep.clone();
+                less.remove(i);
+                index[&less]
+            }).collect();
+            removed.dedup();
+            removed
+        }).collect();
+        Keeps { dice, keeps, index, added, removed }
+    }
+
+    /// The chance of each keep once the rest of the dice are rolled into
+    /// `rolls`, which has a value for each keep of all the dice.
+    fn expect(&self, rolls: &[f64]) -> Vec<f64> {
+        let mut values = rolls.to_vec();
+        for kept in (0..self.keeps.len()).rev() {
+            if self.keeps[kept].len() < self.dice {
+                values[kept] = self.added[kept].iter().map(|&more| values[more]).sum::<f64>() / 6.0;
+            }
+        }
+        values
+    }
+
+    /// The chance of each roll of all the dice with one more reroll than
+    /// in `rolls`, keeping the best dice for it.
+    fn reroll(&self, rolls: &[f64]) -> Vec<f64> {
+        let mut best = self.expect(rolls);
+        for keep in 0..self.keeps.len() {
+            for &less in &self.removed[keep] {
+                if best[less] > best[keep] {
+                    best[keep] = best[less];
+                }
+            }
+        }
+        best
+    }
+}
+
+/// The chance of ending the turn with dice that score in each open slot of
+/// `score`, when the dice that are not `held` are rolled now and
+/// `rolls_left - 1` rerolls follow. With no rolls left, `held` is the
+/// whole roll. Slots are in the order of the sheet.
+pub fn chances(ruleset: &dyn Ruleset, score: &Score, held: &[usize], rolls_left: u8) -> Vec<(Category, f64)> {
+    each_slot(ruleset, score, held, |keeps, mut rolls, held| {
+        if rolls_left == 0 {
+            return rolls[held];
+        }
+        for _ in 1..rolls_left {
+            rolls = keeps.reroll(&rolls);
+        }
+        keeps.expect(&rolls)[held]
+    })
+}
+
+/// The chance of ending the turn with dice that score in each open slot of
+/// `score`, starting from the rolled dice `roll` with `rolls_left` rerolls
+/// to go, which keep the best dice for each slot, or all of them. Slots are
+/// in the order of the sheet.
+pub fn chances_from(ruleset: &dyn Ruleset, score: &Score, roll: &[usize], rolls_left: u8) -> Vec<(Category, f64)> {
+    each_slot(ruleset, score, roll, |keeps, mut rolls, roll| {
+        for _ in 0..rolls_left {
+            rolls = keeps.reroll(&rolls);
+        }
+        rolls[roll]
+    })
+}
+
+/// Works out `chance` for each open slot of `score`, given the keeps of the
+/// ruleset's dice, whether each roll of all of them scores in the slot, and
+/// the index of `dice` among the keeps.
+fn each_slot<F>(ruleset: &dyn Ruleset, score: &Score, dice: &[usize], chance: F) -> Vec<(Category, f64)>
+    where F: Fn(&Keeps, Vec<f64>, usize) -> f64
+{
+    let keeps = Keeps::new(ruleset.dice());
+    let mut dice = dice.to_vec();
+    dice.sort();
+    let dice = keeps.index[&dice];
+
+    score.iter()
+        .filter(|&(_, slot)| slot.is_empty())
+        .map(|(category, _)| {
+            let rolls: Vec<f64> = keeps.keeps.iter().map(|roll| {
+                let scores = roll.len() == keeps.dice
+                    && ruleset.points(score, category, roll).is_some_and(|points| points > 0);
+                if scores { 1.0 } else { 0.0 }
+            }).collect();
+            (category, chance(&keeps, rolls, dice))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Yatzy;
+
+    fn chance_of(chances: &[(Category, f64)], category: Category) -> f64 {
+        chances.iter().find(|&&(open, _)| open == category).unwrap().1
+    }
+
+    #[test]
+    fn chances_from_an_empty_sheet() {
+        let chances = chances(&Yatzy, &Score::for_ruleset(&Yatzy), &[], 3);
+        assert_eq!(chances.len(), 15);
+        assert!((chance_of(&chances, Category::Yatzy) - 0.04603).abs() < 1e-5);
+        assert!((chance_of(&chances, Category::Ones) - 0.93509).abs() < 1e-5);
+        assert_eq!(chance_of(&chances, Category::Chance), 1.0);
+    }
+
+    #[test]
+    fn chances_with_dice_held() {
+        let score = Score::for_ruleset(&Yatzy);
+        let chances = chances(&Yatzy, &score, &[6, 6, 6, 6], 1);
+        assert!((chance_of(&chances, Category::Yatzy) - 1.0 / 6.0).abs() < 1e-12);
+        assert_eq!(chance_of(&chances, Category::FourOfAKind), 1.0);
+        // Once the dice are all rolled, they either score or they don't.
+        let chances = super::chances(&Yatzy, &score, &[1, 2, 3, 4, 5], 0);
+        assert_eq!(chance_of(&chances, Category::SmallStraight), 1.0);
+        assert_eq!(chance_of(&chances, Category::LargeStraight), 0.0);
+    }
+
+    #[test]
+    fn chances_from_a_roll_keep_what_scores() {
+        let score = Score::for_ruleset(&Yatzy);
+        let chances = chances_from(&Yatzy, &score, &[4, 4, 4, 4, 4], 2);
+        assert_eq!(chance_of(&chances, Category::Yatzy), 1.0);
+        assert_eq!(chance_of(&chances, Category::Fours), 1.0);
+        // Holding 6, 6, 6, 6 and rolling the last die twice.
+        let chances = chances_from(&Yatzy, &score, &[6, 6, 6, 6, 1], 2);
+        assert!((chance_of(&chances, Category::Yatzy) - 11.0 / 36.0).abs() < 1e-12);
+        // With no two dice alike, a Yatzy is best chased from any one die.
+        let from_roll = chances_from(&Yatzy, &score, &[1, 2, 3, 5, 6], 2);
+        let from_one = super::chances(&Yatzy, &score, &[6], 2);
+        assert_eq!(chance_of(&from_roll, Category::Yatzy), chance_of(&from_one, Category::Yatzy));
+    }
+}
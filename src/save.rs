@@ -0,0 +1,299 @@
+//////////////////////////////////////////////////////////////////////////
+//                                                                      //
+// Rusty Yacht - A yatzy game for the terminal               R U S T Y  //
+// Copyright (C) 2019  Albin Söderqvist <albin@fripost.org>  U       A  //
+//                                                           S       C  //
+// This game is free software: you can redistribute it       T       H  //
+// and/or modify it under the terms of GNU General Public    Y A C H T  //
+// License as published by the Free Software Foundation,                //
+// either version 3 of the License, or (at your option)                 //
+// any later version.                                                   //
+//                                                                      //
+// Rusty Yacht is distributed in the hope that it will be fun to play,  //
+// but WITHOUT ANY WARRANTY; without even the implied warranty of       //
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the         //
+// GNU General Public License for more details.                         //
+//                                                                      //
+// You should have received a copy of the GNU General Public License    //
+// along with the game. If not, see <https://www.gnu.org/licenses/>.    //
+//                                                                      //
+//////////////////////////////////////////////////////////////////////////
+
+//! Saved games. A game is written as its setup followed by every move made
+//! so far, one per line, and read back by making the moves again:
+//!
+//! ```text
+//! rusty-yacht 1
+//! ruleset yatzy
+//! forced false
+//! seed 2862933555777941757
+//! player - Albin
+//! player greedy Greedy
+//! roll 00000 31425
+//! roll 00110 64425
+//! place Fours
+//! keep 10000
+//! ```
+//!
+//! The last line holds the dice kept for the next roll, if any. Finished
+//! games are recorded in the same format as replays.
+
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, ErrorKind, Write};
+use std::path::PathBuf;
+
+use bot::Strategy;
+use chrono::{Local, NaiveDate};
+use {config_dir, ruleset_by_name, Category, Error, GameState, Move};
+
+/// The first line of a saved game, with the version of the format.
+const HEADER: &str = "rusty-yacht 1";
+
+/// Where the game in progress is saved.
+pub fn path() -> Result<PathBuf, Error> {
+    Ok(config_dir()?.join("save"))
+}
+
+/// Saves `game` to `path`, replacing what was there.
+pub fn store(path: &PathBuf, game: &GameState) -> Result<(), Error> {
+    let mut file = BufWriter::new(File::create(path)?);
+    write(game, &mut file)?;
+    file.flush()?;
+    Ok(())
+}
+
+/// Loads the game saved at `path`.
+pub fn load(path: &PathBuf) -> Result<GameState, Error> {
+    Ok(read(&mut BufReader::new(File::open(path)?))?)
+}
+
+/// Removes the game saved at `path`, if there is one.
+pub fn remove(path: &PathBuf) -> Result<(), Error> {
+    match fs::remove_file(path) {
+        Err(ref error) if error.kind() == ErrorKind::NotFound => Ok(()),
+        result => Ok(result?),
+    }
+}
+
+/// Where finished games are recorded.
+pub fn replay_dir() -> Result<PathBuf, Error> {
+    Ok(config_dir()?.join("replays"))
+}
+
+/// Records `game` as a replay named after the current time, and returns
+/// where it went.
+pub fn record(game: &GameState) -> Result<PathBuf, Error> {
+    let dir = replay_dir()?;
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(Local::now().format("%Y-%m-%d-%H%M%S.replay").to_string());
+    store(&path, game)?;
+    Ok(path)
+}
+
+pub fn write(game: &GameState, out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "{}", HEADER)?;
+    writeln!(out, "ruleset {}", game.ruleset().id())?;
+    writeln!(out, "forced {}", game.is_forced())?;
+    writeln!(out, "seed {}", game.seed())?;
+    if game.is_seed_chosen() {
+        writeln!(out, "seed-chosen true")?;
+    }
+    if let Some(date) = game.daily() {
+        writeln!(out, "daily {}", date.format("%Y-%m-%d"))?;
+    }
+    if game.undo_limit() > 0 {
+        writeln!(out, "undo {}", game.undo_limit())?;
+    }
+    for player in game.players() {
+        let strategy = player.strategy().map_or("-".to_string(), |strategy| strategy.name().to_lowercase());
+        writeln!(out, "player {} {}", strategy, player.name())?;
+    }
+    for step in game.history() {
+        match *step {
+            Move::Roll { ref kept, ref dice } => {
+                writeln!(out, "roll {} {}", mask(kept), digits(dice))?
+            },
+            Move::Place(category) => writeln!(out, "place {:?}", category)?,
+            Move::Strike(category) => writeln!(out, "strike {:?}", category)?,
+        }
+    }
+    let kept: Vec<bool> = game.dice().to_keep.iter().map(|&die| die != 0).collect();
+    if kept.contains(&true) {
+        writeln!(out, "keep {}", mask(&kept))?;
+    }
+    Ok(())
+}
+
+pub fn read(input: &mut dyn BufRead) -> io::Result<GameState> {
+    let mut lines = Vec::new();
+    for line in input.lines() {
+        lines.push(line?);
+    }
+    if lines.first().map(String::as_str) != Some(HEADER) {
+        return Err(invalid("not a saved game of this version".to_string()));
+    }
+
+    let mut ruleset = None;
+    let mut forced = false;
+    let mut seed = 0;
+    let mut seed_chosen = false;
+    let mut daily = None;
+    let mut undo = 0;
+    let mut seats = Vec::new();
+    let mut moves = Vec::new();
+    let mut keep = None;
+    for line in &lines[1..] {
+        let (key, value) = match line.find(' ') {
+            Some(i) => (&line[..i], &line[i + 1..]),
+            None => (line.as_str(), ""),
+        };
+        match key {
+            "ruleset" => ruleset = Some(ruleset_by_name(value)
+                .ok_or_else(|| invalid(format!("unknown ruleset '{}'", value)))?),
+            "forced" => forced = value == "true",
+            "seed" => seed = value.parse().map_err(|_| invalid(format!("bad seed '{}'", value)))?,
+            "seed-chosen" => seed_chosen = value == "true",
+            "daily" => daily = Some(NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .map_err(|_| invalid(format!("bad date '{}'", value)))?),
+            "undo" => undo = value.parse().map_err(|_| invalid(format!("bad undo limit '{}'", value)))?,
+            "player" => seats.push(seat(value)?),
+            "roll" => {
+                let mut fields = value.split(' ');
+                let kept = parse_mask(fields.next().unwrap_or(""))?;
+                let dice = parse_digits(fields.next().unwrap_or(""))?;
+                moves.push(Move::Roll { kept, dice });
+            },
+            "place" => moves.push(Move::Place(category(value)?)),
+            "strike" => moves.push(Move::Strike(category(value)?)),
+            "keep" => keep = Some(parse_mask(value)?),
+            "" => (),
+            _ => return Err(invalid(format!("unknown line '{}'", line))),
+        }
+    }
+
+    let ruleset = ruleset.ok_or_else(|| invalid("no ruleset".to_string()))?;
+    if seats.is_empty() {
+        return Err(invalid("no players".to_string()));
+    }
+    let mut game = GameState::with_seats(ruleset, seats);
+    game.set_forced(forced);
+    if let Some(date) = daily {
+        game.set_daily(date);
+    }
+    if seed_chosen {
+        game.choose_seed(seed);
+    } else {
+        game.set_seed(seed);
+    }
+    game.set_undo_limit(undo);
+    for step in &moves {
+        game.apply(step).map_err(|error| invalid(format!("{:?} cannot be made: {}", step, error)))?;
+    }
+    // The last roll leaves the dice it kept as kept; make them match
+    // what was kept for the next one.
+    if game.rolls() > 0 {
+        let kept = keep.unwrap_or_default();
+        for i in 0..game.dice().current.len() {
+            let wanted = kept.get(i).cloned().unwrap_or(false);
+            if wanted != (game.dice().to_keep[i] != 0) {
+                game.toggle_keep(i).map_err(|error| invalid(error.to_string()))?;
+            }
+        }
+    }
+    Ok(game)
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+fn seat(value: &str) -> io::Result<(String, Option<Strategy>)> {
+    let (strategy, name) = match value.find(' ') {
+        Some(i) => (&value[..i], &value[i + 1..]),
+        None => (value, ""),
+    };
+    match strategy {
+        "-" => Ok((name.to_string(), None)),
+        _ => match Strategy::by_name(strategy) {
+            Some(strategy) => Ok((name.to_string(), Some(strategy))),
+            None => Err(invalid(format!("unknown bot '{}'", strategy))),
+        },
+    }
+}
+
+fn category(name: &str) -> io::Result<Category> {
+    Category::ALL.iter()
+        .cloned()
+        .find(|category| format!("{:?}", category) == name)
+        .ok_or_else(|| invalid(format!("unknown category '{}'", name)))
+}
+
+fn mask(kept: &[bool]) -> String {
+    kept.iter().map(|&kept| if kept { '1' } else { '0' }).collect()
+}
+
+fn parse_mask(mask: &str) -> io::Result<Vec<bool>> {
+    mask.chars()
+        .map(|c| match c {
+            '0' => Ok(false),
+            '1' => Ok(true),
+            _ => Err(invalid(format!("bad dice mask '{}'", mask))),
+        })
+        .collect()
+}
+
+fn digits(dice: &[usize]) -> String {
+    dice.iter().map(|die| die.to_string()).collect()
+}
+
+fn parse_digits(digits: &str) -> io::Result<Vec<usize>> {
+    digits.chars()
+        .map(|c| match c.to_digit(10) {
+            Some(die @ 1..=6) => Ok(die as usize),
+            _ => Err(invalid(format!("bad dice '{}'", digits))),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Yatzy;
+
+    #[test]
+    fn written_game_reads_back() {
+        let seats = vec![("Albin".to_string(), None), ("Greedy".to_string(), Some(Strategy::Greedy))];
+        let mut game = GameState::with_seats(Box::new(Yatzy), seats);
+        game.set_forced(true);
+        game.choose_seed(42);
+        game.set_undo_limit(2);
+        game.roll().unwrap();
+        game.strike(Category::Ones).unwrap();
+        game.roll().unwrap();
+        game.toggle_keep(1).unwrap();
+        game.roll().unwrap();
+        game.toggle_keep(3).unwrap();
+
+        let mut written = Vec::new();
+        write(&game, &mut written).unwrap();
+        let read_back = read(&mut &written[..]).unwrap();
+        assert_eq!(read_back.history(), game.history());
+        assert_eq!(read_back.dice().current, game.dice().current);
+        assert_eq!(read_back.dice().to_keep, game.dice().to_keep);
+        assert_eq!(read_back.current_player(), 1);
+        assert_eq!(read_back.players()[1].strategy(), Some(Strategy::Greedy));
+        assert_eq!(read_back.seed(), 42);
+        assert!(read_back.is_forced() && read_back.is_seed_chosen());
+        assert_eq!(read_back.undo_limit(), 2);
+
+        let mut again = Vec::new();
+        write(&read_back, &mut again).unwrap();
+        assert_eq!(String::from_utf8(again).unwrap(), String::from_utf8(written).unwrap());
+    }
+
+    #[test]
+    fn other_files_are_refused() {
+        assert!(read(&mut &b"rusty-yacht 0\nruleset yatzy\n"[..]).is_err());
+        assert!(read(&mut &b"rusty-yacht 1\nruleset yatzy\nplace Ones\n"[..]).is_err());
+    }
+}
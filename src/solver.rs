@@ -0,0 +1,438 @@
+//////////////////////////////////////////////////////////////////////////
+//                                                                      //
+// Rusty Yacht - A yatzy game for the terminal               R U S T Y  //
+// Copyright (C) 2019  Albin Söderqvist <albin@fripost.org>  U       A  //
+//                                                           S       C  //
+// This game is free software: you can redistribute it       T       H  //
+// and/or modify it under the terms of GNU General Public    Y A C H T  //
+// License as published by the Free Software Foundation,                //
+// either version 3 of the License, or (at your option)                 //
+// any later version.                                                   //
+//                                                                      //
+// Rusty Yacht is distributed in the hope that it will be fun to play,  //
+// but WITHOUT ANY WARRANTY; without even the implied warranty of       //
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the         //
+// GNU General Public License for more details.                         //
+//                                                                      //
+// You should have received a copy of the GNU General Public License    //
+// along with the game. If not, see <https://www.gnu.org/licenses/>.    //
+//                                                                      //
+//////////////////////////////////////////////////////////////////////////
+
+//! Optimal solitaire play. Between turns a game is fully described by
+//! which slots are used and the upper section sum so far, so the expected
+//! number of points still to come from each such state can be worked out
+//! backwards from the full sheet. Within a turn the dice and the rolls left
+//! are added on top, which is cheap enough to do on demand.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::thread;
+
+use {config_dir, outcomes, Category, Error, Ruleset, Score, SlotState, Validator, Yatzy};
+
+/// Identifies the cache file format.
+const MAGIC: &[u8; 8] = b"RYSOLV1\0";
+
+/// Expected values of every state of a solitaire game under optimal play.
+pub struct Solver {
+    categories: Vec<Category>,
+    /// Positions in `categories` of the upper section slots.
+    upper: Vec<usize>,
+    threshold: usize,
+    bonus: f32,
+    /// Every multiset of up to five dice, sorted, indexed as in `keeps`.
+    keeps: Vec<Vec<usize>>,
+    index: HashMap<Vec<usize>, usize>,
+    /// The keep with one more die of each face, for keeps under five dice.
+    added: Vec<[usize; 6]>,
+    /// The keeps with one die less.
+    removed: Vec<Vec<usize>>,
+    /// Points of each category for the keeps of five dice.
+    points: Vec<Vec<u16>>,
+    /// Expected points still to come at the start of a turn, by used slots
+    /// and capped upper sum.
+    values: Vec<f32>,
+}
+
+impl Solver {
+    /// Whether the solver can play `ruleset`, which needs five dice, two
+    /// rerolls and no rules beyond the validators and the upper bonus.
+    pub fn supports(ruleset: &dyn Ruleset) -> bool {
+        ruleset.dice() == 5 && ruleset.rerolls() == 2 && !ruleset.saves_rolls()
+            && ruleset.yatzy_bonus() == 0 && ruleset.categories().len() <= 16
+    }
+
+    /// Whether `for_ruleset` has a solver for `ruleset`, which is only kept
+    /// for the default ruleset.
+    pub fn available(ruleset: &dyn Ruleset) -> bool {
+        ruleset.id() == Yatzy.id()
+    }
+
+    /// The solver for `ruleset`, if available. It is read from the cache on
+    /// disk, or worked out and cached the first time.
+    pub fn for_ruleset(ruleset: &dyn Ruleset) -> Option<&'static Solver> {
+        static SOLVER: OnceLock<Solver> = OnceLock::new();
+        if !Solver::available(ruleset) {
+            return None;
+        }
+        Some(SOLVER.get_or_init(|| {
+            // Without a place for the cache the solver is worked out anew.
+            let path = Solver::cache_path(&Yatzy).ok();
+            if let Some(solver) = path.as_ref().and_then(|path| Solver::load(&Yatzy, path)) {
+                return solver;
+            }
+            let solver = Solver::new(&Yatzy);
+            if let Some(path) = path {
+                if let Err(e) = solver.save(&path) {
+                    eprintln!("Couldn't cache the solver: {}", e);
+                }
+            }
+            solver
+        }))
+    }
+
+    pub fn cache_path(ruleset: &dyn Ruleset) -> Result<PathBuf, Error> {
+        Ok(config_dir()?.join(format!("solver-{}", ruleset.id())))
+    }
+
+    /// Works out the expected value of every state, which takes a while.
+    pub fn new(ruleset: &dyn Ruleset) -> Solver {
+        let mut solver = Solver::tables(ruleset);
+        let slots = solver.categories.len();
+        let width = solver.threshold + 1;
+        solver.values = vec![0.0; (1 << slots) * width];
+        let reachable = solver.reachable();
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+
+        // States with the same number of used slots only depend on states
+        // with more, so each such layer can be split between threads.
+        for filled in (0..slots).rev() {
+            let layer: Vec<usize> = (0..1 << slots)
+                .filter(|used: &usize| used.count_ones() as usize == filled)
+                .collect();
+            let chunk = layer.len().div_ceil(threads);
+            let solved: Vec<Vec<(usize, f32)>> = thread::scope(|scope| {
+                let solver = &solver;
+                let reachable = &reachable;
+                let workers: Vec<_> = layer.chunks(chunk)
+                    .map(|states| scope.spawn(move || {
+                        let mut solved = Vec::new();
+                        for &used in states {
+                            let upper_used = solver.upper_used(used);
+                            for upper in (0..width).filter(|&upper| reachable[upper_used][upper]) {
+                                let rolls = solver.roll_values(used, upper);
+                                let start = solver.reroll(&solver.reroll(&rolls).1).1;
+                                solved.push((used * width + upper, solver.expect(&start)[0]));
+                            }
+                        }
+                        solved
+                    }))
+                    .collect();
+                workers.into_iter().map(|worker| worker.join().unwrap()).collect()
+            });
+            for (state, value) in solved.into_iter().flatten() {
+                solver.values[state] = value;
+            }
+        }
+        solver
+    }
+
+    /// Reads the values cached by `save`, if they are there and fit.
+    pub fn load(ruleset: &dyn Ruleset, path: &PathBuf) -> Option<Solver> {
+        let mut bytes = Vec::new();
+        File::open(path).ok()?.read_to_end(&mut bytes).ok()?;
+        let mut solver = Solver::tables(ruleset);
+        let count = (1 << solver.categories.len()) * (solver.threshold + 1);
+        if bytes.len() != MAGIC.len() + 4 * count || &bytes[..MAGIC.len()] != MAGIC {
+            return None;
+        }
+        solver.values = bytes[MAGIC.len()..].chunks(4)
+            .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect();
+        Some(solver)
+    }
+
+    pub fn save(&self, path: &PathBuf) -> Result<(), Error> {
+        let mut bytes = MAGIC.to_vec();
+        for value in &self.values {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        File::create(path)?.write_all(&bytes)?;
+        Ok(())
+    }
+
+    /// The expected final total of `score` at the start of a turn.
+    pub fn expected(&self, score: &Score) -> f64 {
+        let (used, upper) = self.state(score);
+        score.total() as f64 + self.value(used, upper) as f64
+    }
+
+    /// The expected final total for scoring `dice` in each open slot,
+    /// best first.
+    pub fn slot_values(&self, score: &Score, dice: &[usize]) -> Vec<(Category, f64)> {
+        let (used, upper) = self.state(score);
+        let roll = self.index[&sorted(dice)];
+        let mut slots: Vec<(Category, f64)> = self.open(used)
+            .map(|slot| {
+                let value = self.score_in(used, upper, roll, slot);
+                (self.categories[slot], score.total() as f64 + value as f64)
+            })
+            .collect();
+        slots.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        slots
+    }
+
+    /// The expected final total for striking out `category`, scoring
+    /// nothing in it whatever the dice are.
+    pub fn strike_value(&self, score: &Score, category: Category) -> f64 {
+        let (used, upper) = self.state(score);
+        let slot = self.categories.iter().position(|&other| other == category).unwrap();
+        score.total() as f64 + self.value(used | 1 << slot, upper) as f64
+    }
+
+    /// The expected final total for keeping each set of `dice` with
+    /// `rerolls` rerolls left, best first. Keeping all dice means not
+    /// rerolling at all.
+    pub fn keep_values(&self, score: &Score, dice: &[usize], rerolls: u8) -> Vec<(Vec<bool>, f64)> {
+        let (used, upper) = self.state(score);
+        let mut rolls = self.roll_values(used, upper);
+        for _ in 1..rerolls {
+            rolls = self.reroll(&rolls).1;
+        }
+        let kept_values = self.expect(&rolls);
+
+        let mut seen = Vec::new();
+        let mut keeps = Vec::new();
+        for mask in (0..1 << dice.len()).rev() {
+            let keep: Vec<bool> = (0..dice.len()).map(|i| mask & (1 << i) != 0).collect();
+            let kept: Vec<usize> = (0..dice.len()).filter(|&i| keep[i]).map(|i| dice[i]).collect();
+            let kept = self.index[&sorted(&kept)];
+            if seen.contains(&kept) {
+                continue;
+            }
+            seen.push(kept);
+            keeps.push((keep, score.total() as f64 + kept_values[kept] as f64));
+        }
+        keeps.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        keeps
+    }
+
+    /// Builds the lookup tables, leaving the values empty.
+    fn tables(ruleset: &dyn Ruleset) -> Solver {
+        assert!(Solver::supports(ruleset), "the solver does not support {}", ruleset.name());
+        let categories = ruleset.categories().to_vec();
+        let validators: Vec<Validator> = categories.iter()
+            .map(|&category| ruleset.validator(category))
+            .collect();
+        let keeps: Vec<Vec<usize>> = (0..6).flat_map(outcomes).collect();
+        let index: HashMap<Vec<usize>, usize> = keeps.iter()
+            .cloned()
+            .enumerate()
+            .map(|(i, keep)| (keep, i))
+            .collect();
+        let added = keeps.iter()
+            .map(|keep| {
+                let mut added = [0; 6];
+                if keep.len() < 5 {
+                    for face in 1..7 {
+                        let mut more = keep.clone();
+                        more.push(face);
+                        added[face - 1] = index[&sorted(&more)];
+                    }
+                }
+                added
+            })
+            .collect();
+        let removed = keeps.iter()
+            .map(|keep| {
+                let mut faces = keep.clone();
+                faces.dedup();
+                faces.iter()
+                    .map(|&face| {
+                        let mut less = keep.clone();
+                        let i = less.iter().position(|&die| die == face).unwrap();
+                        less.remove(i);
+                        index[&less]
+                    })
+                    .collect()
+            })
+            .collect();
+        let points = keeps.iter()
+            .map(|keep| {
+                validators.iter()
+                    .map(|validator| if keep.len() == 5 { validator(keep).unwrap_or(0) as u16 } else { 0 })
+                    .collect()
+            })
+            .collect();
+        Solver {
+            upper: (0..categories.len()).filter(|&i| categories[i].is_upper()).collect(),
+            categories,
+            threshold: ruleset.bonus_threshold() as usize,
+            bonus: ruleset.bonus() as f32,
+            keeps,
+            index,
+            added,
+            removed,
+            points,
+            values: Vec::new(),
+        }
+    }
+
+    /// Used slots and capped upper sum of a score sheet.
+    fn state(&self, score: &Score) -> (usize, usize) {
+        let used = (0..self.categories.len())
+            .filter(|&i| score.get(self.categories[i]) != Some(SlotState::Empty))
+            .fold(0, |used, i| used | 1 << i);
+        (used, (score.sum() as usize).min(self.threshold))
+    }
+
+    fn value(&self, used: usize, upper: usize) -> f32 {
+        self.values[used * (self.threshold + 1) + upper]
+    }
+
+    fn open(&self, used: usize) -> impl Iterator<Item = usize> + '_ {
+        (0..self.categories.len()).filter(move |&i| used & (1 << i) == 0)
+    }
+
+    /// The upper section slots among `used`, numbered 0 to 63.
+    fn upper_used(&self, used: usize) -> usize {
+        self.upper.iter()
+            .enumerate()
+            .filter(|&(_, &slot)| used & (1 << slot) != 0)
+            .fold(0, |bits, (i, _)| bits | 1 << i)
+    }
+
+    /// Which capped upper sums can be had with each set of used upper slots.
+    fn reachable(&self) -> Vec<Vec<bool>> {
+        let mut reachable = vec![vec![false; self.threshold + 1]; 1 << self.upper.len()];
+        reachable[0][0] = true;
+        for bits in 0..reachable.len() {
+            for (i, &slot) in self.upper.iter().enumerate() {
+                if bits & (1 << i) != 0 {
+                    continue;
+                }
+                let face = self.categories[slot].face().unwrap();
+                for upper in 0..=self.threshold {
+                    if reachable[bits][upper] {
+                        for count in 0..6 {
+                            let next = (upper + face * count).min(self.threshold);
+                            reachable[bits | 1 << i][next] = true;
+                        }
+                    }
+                }
+            }
+        }
+        reachable
+    }
+
+    /// Points now plus expected points to come for scoring `roll` in `slot`.
+    fn score_in(&self, used: usize, upper: usize, roll: usize, slot: usize) -> f32 {
+        let points = self.points[roll][slot] as usize;
+        let mut next = upper;
+        let mut bonus = 0.0;
+        if self.categories[slot].is_upper() {
+            next = (upper + points).min(self.threshold);
+            if upper < self.threshold && next == self.threshold {
+                bonus = self.bonus;
+            }
+        }
+        points as f32 + bonus + self.value(used | 1 << slot, next)
+    }
+
+    /// The best value of each roll of five dice with no rerolls left.
+    fn roll_values(&self, used: usize, upper: usize) -> Vec<f32> {
+        let mut values = vec![0.0; self.keeps.len()];
+        for (roll, keep) in self.keeps.iter().enumerate() {
+            if keep.len() == 5 {
+                values[roll] = self.open(used)
+                    .map(|slot| self.score_in(used, upper, roll, slot))
+                    .fold(f32::MIN, f32::max);
+            }
+        }
+        values
+    }
+
+    /// The expected value of each keep when the rest of the dice are rolled
+    /// into `rolls`.
+    fn expect(&self, rolls: &[f32]) -> Vec<f32> {
+        let mut values = rolls.to_vec();
+        for kept in (0..self.keeps.len()).rev() {
+            if self.keeps[kept].len() < 5 {
+                values[kept] = self.added[kept].iter().map(|&more| values[more]).sum::<f32>() / 6.0;
+            }
+        }
+        values
+    }
+
+    /// The values of each keep, and of each roll of five dice with one more
+    /// reroll left than in `rolls`.
+    fn reroll(&self, rolls: &[f32]) -> (Vec<f32>, Vec<f32>) {
+        let kept = self.expect(rolls);
+        let mut best = kept.clone();
+        for keep in 0..self.keeps.len() {
+            for &less in &self.removed[keep] {
+                if best[less] > best[keep] {
+                    best[keep] = best[less];
+                }
+            }
+        }
+        (kept, best)
+    }
+}
+
+fn sorted(dice: &[usize]) -> Vec<usize> {
+    let mut dice = dice.to_vec();
+    dice.sort();
+    dice
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Yatzy cut down to the slots given, small enough to solve in a test.
+    struct Slots(&'static [Category]);
+
+    impl Ruleset for Slots {
+        fn name(&self) -> &'static str {
+            "Test"
+        }
+
+        fn categories(&self) -> &'static [Category] {
+            self.0
+        }
+
+        fn validator(&self, category: Category) -> Validator {
+            Yatzy.validator(category)
+        }
+    }
+
+    #[test]
+    fn expected_totals_of_a_single_slot() {
+        // A die is rerolled below 5 and then below 4, which averages 14/3.
+        let chance = Slots(&[Category::Chance]);
+        let solver = Solver::new(&chance);
+        assert!((solver.expected(&Score::for_ruleset(&chance)) - 70.0 / 3.0).abs() < 1e-4);
+
+        // Five of a kind in three rolls comes up 4.6 % of the time.
+        let yatzy = Slots(&[Category::Yatzy]);
+        let solver = Solver::new(&yatzy);
+        assert!((solver.expected(&Score::for_ruleset(&yatzy)) - 50.0 * 0.046029).abs() < 1e-3);
+    }
+
+    #[test]
+    fn best_keeps_and_slots() {
+        let ruleset = Slots(&[Category::Chance, Category::Yatzy]);
+        let solver = Solver::new(&ruleset);
+        let score = Score::for_ruleset(&ruleset);
+        let keeps = solver.keep_values(&score, &[6, 6, 2, 6, 6], 2);
+        assert_eq!(keeps[0].0, vec![true, true, false, true, true]);
+        let slots = solver.slot_values(&score, &[6, 6, 6, 6, 6]);
+        assert_eq!(slots[0].0, Category::Yatzy);
+        assert!((slots[0].1 - (50.0 + 70.0 / 3.0)).abs() < 1e-3);
+    }
+}
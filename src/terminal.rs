@@ -0,0 +1,115 @@
+//////////////////////////////////////////////////////////////////////////
+//                                                                      //
+// Rusty Yacht - A yatzy game for the terminal               R U S T Y  //
+// Copyright (C) 2019  Albin Söderqvist <albin@fripost.org>  U       A  //
+//                                                           S       C  //
+// This game is free software: you can redistribute it       T       H  //
+// and/or modify it under the terms of GNU General Public    Y A C H T  //
+// License as published by the Free Software Foundation,                //
+// either version 3 of the License, or (at your option)                 //
+// any later version.                                                   //
+//                                                                      //
+// Rusty Yacht is distributed in the hope that it will be fun to play,  //
+// but WITHOUT ANY WARRANTY; without even the implied warranty of       //
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the         //
+// GNU General Public License for more details.                         //
+//                                                                      //
+// You should have received a copy of the GNU General Public License    //
+// along with the game. If not, see <https://www.gnu.org/licenses/>.    //
+//                                                                      //
+//////////////////////////////////////////////////////////////////////////
+
+//! Giving the terminal back the way it was found. The game switches raw
+//! mode on and off and hides the cursor as it goes; `Guard` remembers the
+//! settings from before and puts them back when the game ends, however it
+//! ends.
+
+use std::io::{self, Stdout, Write};
+use std::mem;
+use std::panic;
+use std::process;
+use std::sync::OnceLock;
+
+use termion::cursor;
+use termion::raw::RawTerminal;
+
+/// The terminal settings from before the game started.
+static ORIGINAL: OnceLock<libc::termios> = OnceLock::new();
+
+/// Restores the terminal when dropped, and on a panic or a SIGTERM,
+/// SIGHUP or SIGINT until then.
+pub struct Guard {
+    _private: (),
+}
+
+impl Guard {
+    pub fn new() -> io::Result<Guard> {
+        let mut termios: libc::termios = unsafe { mem::zeroed() };
+        if unsafe { libc::tcgetattr(libc::STDOUT_FILENO, &mut termios) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let _ = ORIGINAL.set(termios);
+
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            restore();
+            hook(info);
+        }));
+        let handler = on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        for &signal in &[libc::SIGTERM, libc::SIGHUP, libc::SIGINT] {
+            unsafe {
+                libc::signal(signal, handler);
+            }
+        }
+        Ok(Guard { _private: () })
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+/// Puts back the settings from before the game and shows the cursor. This
+/// is safe to call from a signal handler.
+pub fn restore() {
+    if let Some(termios) = ORIGINAL.get() {
+        unsafe {
+            libc::tcsetattr(libc::STDOUT_FILENO, libc::TCSANOW, termios);
+        }
+    }
+    let show = b"\x1b[?25h";
+    unsafe {
+        libc::write(libc::STDOUT_FILENO, show.as_ptr() as *const libc::c_void, show.len());
+    }
+}
+
+/// Restores the terminal and exits, which `process::exit` alone would not
+/// do since it skips the guard.
+pub fn exit(code: i32) -> ! {
+    restore();
+    process::exit(code);
+}
+
+/// Stops the game the way Ctrl+z does in a shell, which raw mode turns into
+/// a key press instead, and goes back to raw mode when it is resumed. The
+/// caller draws the screen again after.
+pub fn suspend(stdout: &mut RawTerminal<Stdout>) -> io::Result<()> {
+    stdout.suspend_raw_mode()?;
+    write!(stdout, "{}", cursor::Show)?;
+    stdout.flush()?;
+    unsafe {
+        libc::raise(libc::SIGTSTP);
+    }
+    stdout.activate_raw_mode()?;
+    write!(stdout, "{}", cursor::Hide)?;
+    stdout.flush()
+}
+
+extern "C" fn on_signal(signal: libc::c_int) {
+    restore();
+    unsafe {
+        libc::_exit(128 + signal);
+    }
+}
@@ -0,0 +1,133 @@
+//////////////////////////////////////////////////////////////////////////
+//                                                                      //
+// Rusty Yacht - A yatzy game for the terminal               R U S T Y  //
+// Copyright (C) 2019  Albin Söderqvist <albin@fripost.org>  U       A  //
+//                                                           S       C  //
+// This game is free software: you can redistribute it       T       H  //
+// and/or modify it under the terms of GNU General Public    Y A C H T  //
+// License as published by the Free Software Foundation,                //
+// either version 3 of the License, or (at your option)                 //
+// any later version.                                                   //
+//                                                                      //
+// Rusty Yacht is distributed in the hope that it will be fun to play,  //
+// but WITHOUT ANY WARRANTY; without even the implied warranty of       //
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the         //
+// GNU General Public License for more details.                         //
+//                                                                      //
+// You should have received a copy of the GNU General Public License    //
+// along with the game. If not, see <https://www.gnu.org/licenses/>.    //
+//                                                                      //
+//////////////////////////////////////////////////////////////////////////
+
+//! Colors for the parts of the screen that say something at a glance: the
+//! dice that are kept, the cursor, which slots the dice would score in and
+//! how the upper section is doing towards the bonus.
+
+use std::env;
+use std::fmt;
+use std::io;
+
+use termion::color::{self, AnsiValue};
+
+/// A color for each part of the screen, as a value of the 256-color
+/// palette.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// The name in the config file.
+    pub name: &'static str,
+    /// The pips of kept dice.
+    pub held: u8,
+    /// The cursor under the dice and on the score sheet.
+    pub cursor: u8,
+    /// Open slots the dice would score points in.
+    pub scorable: u8,
+    /// Open slots the dice would score nothing in or may not go in.
+    pub zero: u8,
+    /// The upper section sum once it has reached the bonus.
+    pub bonus_made: u8,
+    /// The upper section sum while it is at least three of each number
+    /// so far, or as many as the ruleset needs.
+    pub bonus_on_track: u8,
+    /// The upper section sum while it is behind that, or has missed the
+    /// bonus.
+    pub bonus_behind: u8,
+}
+
+/// The themes to pick from, the default first.
+pub const THEMES: &[Theme] = &[
+    Theme {
+        name: "default",
+        held: 11,
+        cursor: 14,
+        scorable: 2,
+        zero: 8,
+        bonus_made: 10,
+        bonus_on_track: 2,
+        bonus_behind: 1,
+    },
+    Theme {
+        name: "high-contrast",
+        held: 11,
+        cursor: 15,
+        scorable: 10,
+        zero: 9,
+        bonus_made: 10,
+        bonus_on_track: 14,
+        bonus_behind: 9,
+    },
+    // Solarized yellow, blue, green, base01, cyan and red.
+    Theme {
+        name: "solarized",
+        held: 136,
+        cursor: 33,
+        scorable: 64,
+        zero: 240,
+        bonus_made: 64,
+        bonus_on_track: 37,
+        bonus_behind: 160,
+    },
+    // The Okabe-Ito colors, told apart with any color vision: orange, sky
+    // blue, bluish green, grey, blue and vermillion.
+    Theme {
+        name: "colorblind",
+        held: 214,
+        cursor: 117,
+        scorable: 36,
+        zero: 244,
+        bonus_made: 32,
+        bonus_on_track: 117,
+        bonus_behind: 166,
+    },
+];
+
+impl Theme {
+    pub fn by_name(name: &str) -> Option<Theme> {
+        THEMES.iter().find(|theme| theme.name.eq_ignore_ascii_case(name)).cloned()
+    }
+}
+
+/// Whether the screen can show colors: not when NO_COLOR is set, when the
+/// output goes somewhere other than a terminal, or when the terminal is a
+/// dumb one.
+pub fn supported() -> bool {
+    if env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+        return false;
+    }
+    if !termion::is_tty(&io::stdout()) {
+        return false;
+    }
+    env::var("TERM").is_ok_and(|term| !term.is_empty() && term != "dumb")
+}
+
+/// Shows `text` in `color`, or as it is without one. Only the foreground
+/// is reset after, so reverse video around it stays on.
+pub struct Paint<T>(pub Option<u8>, pub T);
+
+impl<T: fmt::Display> fmt::Display for Paint<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Some(value) => write!(f, "{}{}{}", color::Fg(AnsiValue(value)), self.1, color::Fg(color::Reset)),
+            None => write!(f, "{}", self.1),
+        }
+    }
+}
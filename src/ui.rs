@@ -0,0 +1,1183 @@
+//////////////////////////////////////////////////////////////////////////
+//                                                                      //
+// Rusty Yacht - A yatzy game for the terminal               R U S T Y  //
+// Copyright (C) 2019  Albin Söderqvist <albin@fripost.org>  U       A  //
+//                                                           S       C  //
+// This game is free software: you can redistribute it       T       H  //
+// and/or modify it under the terms of GNU General Public    Y A C H T  //
+// License as published by the Free Software Foundation,                //
+// either version 3 of the License, or (at your option)                 //
+// any later version.                                                   //
+//                                                                      //
+// Rusty Yacht is distributed in the hope that it will be fun to play,  //
+// but WITHOUT ANY WARRANTY; without even the implied warranty of       //
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the         //
+// GNU General Public License for more details.                         //
+//                                                                      //
+// You should have received a copy of the GNU General Public License    //
+// along with the game. If not, see <https://www.gnu.org/licenses/>.    //
+//                                                                      //
+//////////////////////////////////////////////////////////////////////////
+
+//! The termion front-end. Everything that reads keys or draws on the
+//! terminal lives here; the game rules are in `GameState`.
+
+use std::io::{self, Write, stdout, stdin};
+use std::thread;
+use termion::clear;
+use termion::event::Key;
+use termion::raw::IntoRawMode;
+use termion::style;
+use termion::input::TermRead;
+
+use analysis::{self, Choice, Decision};
+use config;
+use keys::{Action, Screen};
+use save;
+use solver::Solver;
+use terminal;
+use theme::{Paint, Theme};
+use chrono::NaiveDate;
+use {Category, Daily, Dice, Entry, Error, GameError, GameState, Highscore, Layout, Move, Ruleset, Score, SlotState};
+
+pub enum DiceSelectStatus {
+    Complete,
+    Exit,
+    Hint,
+    Incomplete,
+}
+
+pub enum SlotSelectStatus {
+    AlreadySelected,
+    NotAllowed,
+    Exit,
+    Invalid,
+    ConfirmStrike,
+    Complete,
+    Hint,
+    Incomplete,
+}
+
+impl Score {
+    /// Prints the score sheets side by side, one column per player, with
+    /// the cursor in the column of player `current`. `points` has what the
+    /// dice would score in each slot of that player they may go in, which
+    /// is shown there in parentheses. The other open slots are greyed out.
+    pub fn print_sheets(sheets: &[(&str, &Score)], ruleset: &dyn Ruleset,
+                        current: usize, cursor: Option<Category>, points: &[(Category, u16)]) {
+        let layout = SheetLayout::new(sheets.len());
+        let width = layout.width;
+        let scores: Vec<&Score> = sheets.iter().map(|&(_, score)| score).collect();
+        let each = |f: &dyn Fn(&Score) -> SlotState| -> Vec<Cell> {
+            scores.iter().map(|score| Cell::from(f(score))).collect()
+        };
+        let max_sum: u16 = scores[0].iter()
+            .filter(|&(category, _)| category.is_upper())
+            .map(|(category, _)| ruleset.max_points(category))
+            .sum();
+        let max_lower: u16 = scores[0].iter()
+            .filter(|&(category, _)| !category.is_upper())
+            .map(|(category, _)| ruleset.max_points(category))
+            .sum();
+        let upper = |score: &Score| score.sum() + score.bonus().points();
+        let faces = |score: &Score, filled: bool| -> u16 {
+            score.iter()
+                .filter(|&(category, slot)| category.is_upper() && (!filled || !slot.is_empty()))
+                .filter_map(|(category, _)| category.face())
+                .sum::<usize>() as u16
+        };
+        // On track for the bonus means as many of each number as it takes
+        // in every upper slot filled in so far.
+        let progress = |score: &Score| match score.bonus() {
+            SlotState::Scored(_) => color(|theme| theme.bonus_made),
+            SlotState::Empty if score.sum() * faces(score, false) >= ruleset.bonus_threshold() * faces(score, true) => {
+                color(|theme| theme.bonus_on_track)
+            },
+            _ => color(|theme| theme.bonus_behind),
+        };
+        let header: Vec<String> = if sheets.len() == 1 {
+            vec!["Score".to_string()]
+        } else {
+            sheets.iter().map(|&(name, _)| name.chars().take(width - 2).collect()).collect()
+        };
+
+        println!("╔{}╗", "═".repeat(layout.inner_width()));
+        println!("║ RUSTY YACHT{:>w$} ║", ruleset.name(), w = layout.inner_width() - 13);
+        layout.line('╠', '═', '╦', '╣');
+        print!("║                       Max ║");
+        for name in header {
+            print!(" {:>w$} ║", name, w = width - 2);
+        }
+        println!();
+        layout.line('╟', '─', '╫', '╢');
+        for (category, _) in scores[0].iter() {
+            let highlighted = if cursor == Some(category) { Some(current) } else { None };
+            let mut cells = each(&|score| score.get(category).unwrap());
+            let preview = points.iter().find(|&&(open, _)| open == category).map(|&(_, points)| points);
+            let blocked = !points.is_empty() && scores[current].get(category) == Some(SlotState::Empty);
+            let name_color = match preview {
+                Some(0) => color(|theme| theme.zero),
+                Some(_) => color(|theme| theme.scorable),
+                None if blocked => color(|theme| theme.zero),
+                None => None,
+            };
+            if let Some(points) = preview {
+                let greyed = if points == 0 { name_color } else { None };
+                cells[current] = Cell { text: format!("({})", points), color: greyed };
+            }
+            layout.row(ruleset.label(category), Some(ruleset.max_points(category)), &cells, highlighted,
+                       name_color);
+            if category != Category::Sixes {
+                continue;
+            }
+            layout.line('╟', '─', '╫', '╢');
+            let mut sums = each(&|score| points_or_empty(score.sum()));
+            for (cell, score) in sums.iter_mut().zip(&scores) {
+                cell.color = progress(score);
+            }
+            layout.row("Sum", Some(max_sum), &sums, None, None);
+            layout.row("Bonus", Some(ruleset.bonus()), &each(&|score| score.bonus()), None, None);
+            if ruleset.layout() == Layout::American {
+                layout.row("Upper Total", Some(max_sum + ruleset.bonus()),
+                           &each(&|score| points_or_empty(upper(score))), None, None);
+                layout.line('╟', '─', '╫', '╢');
+            }
+        }
+        layout.line('╟', '─', '╫', '╢');
+        if ruleset.layout() == Layout::American {
+            layout.row(&format!("{} Bonus", ruleset.label(Category::Yatzy)), None,
+                       &each(&|score| points_or_empty(score.yatzy_bonus())), None, None);
+            layout.row("Lower Total", Some(max_lower),
+                       &each(&|score| points_or_empty(score.total() - upper(score))), None, None);
+            layout.row("Upper Total", Some(max_sum + ruleset.bonus()),
+                       &each(&|score| points_or_empty(upper(score))), None, None);
+            layout.line('╟', '─', '╫', '╢');
+            layout.row("Grand Total", Some(max_sum + ruleset.bonus() + max_lower),
+                       &each(&|score| points_or_empty(score.total())), None, None);
+        } else {
+            layout.row("Total", Some(max_sum + ruleset.bonus() + max_lower),
+                       &each(&|score| points_or_empty(score.total())), None, None);
+        }
+        layout.line('╚', '═', '╩', '╝');
+    }
+}
+
+/// Prints every player's score sheet with the cursor in the column of the
+/// player whose turn it is.
+pub fn print_game(game: &GameState, cursor: Option<Category>) {
+    let sheets: Vec<(&str, &Score)> = game.players().iter()
+        .map(|player| (player.name(), player.score()))
+        .collect();
+    Score::print_sheets(&sheets, game.ruleset(), game.current_player(), cursor, &open_points(game, cursor));
+}
+
+/// What the dice would score in each open slot of the player whose turn it
+/// is, while they pick one.
+fn open_points(game: &GameState, cursor: Option<Category>) -> Vec<(Category, u16)> {
+    if cursor.is_none() || game.rolls() == 0 {
+        return Vec::new();
+    }
+    game.score().iter()
+        .filter(|&(category, slot)| slot.is_empty() && game.may_use(category))
+        .map(|(category, _)| (category, game.points(category).unwrap_or(0)))
+        .collect()
+}
+
+/// The color `pick` chooses from the theme in use, if there are colors.
+fn color(pick: fn(&Theme) -> u8) -> Option<u8> {
+    config::get().theme.as_ref().map(pick)
+}
+
+/// The mark under the die the cursor is on.
+fn dice_cursor() -> Paint<&'static str> {
+    Paint(color(|theme| theme.cursor), "●━━━━━━━●")
+}
+
+/// What a slot shows on the score sheet.
+struct Cell {
+    text: String,
+    color: Option<u8>,
+}
+
+impl From<SlotState> for Cell {
+    fn from(slot: SlotState) -> Cell {
+        let text = match slot {
+            SlotState::Empty => String::new(),
+            SlotState::Scored(points) => points.to_string(),
+            SlotState::Struck => "–".to_string(),
+        };
+        Cell { text, color: None }
+    }
+}
+
+/// Column widths of a score sheet table.
+struct SheetLayout {
+    width: usize,
+    columns: usize,
+}
+
+impl SheetLayout {
+    fn new(columns: usize) -> SheetLayout {
+        let width = if columns == 1 { 19 } else { 11 };
+        SheetLayout { width, columns }
+    }
+
+    fn inner_width(&self) -> usize {
+        27 + self.columns * (self.width + 1)
+    }
+
+    fn line(&self, left: char, fill: char, cross: char, right: char) {
+        let mut line = format!("{}{}", left, fill.to_string().repeat(27));
+        for _ in 0..self.columns {
+            line.push(cross);
+            line.push_str(&fill.to_string().repeat(self.width));
+        }
+        line.push(right);
+        println!("{}", line);
+    }
+
+    /// Prints a row with `name` in `name_color`, if any, and a cell for
+    /// each player.
+    fn row(&self, name: &str, max: Option<u16>, cells: &[Cell], highlighted: Option<usize>,
+           name_color: Option<u8>) {
+        let max = max.map_or(String::new(), |max| max.to_string());
+        let cursor = color(|theme| theme.cursor);
+        if highlighted.is_some() {
+            print!("║{}{}{}║", style::Invert, Paint(cursor, format!(" {:<21}{:>4} ", name, max)), style::Reset);
+        } else {
+            print!("║ {}{:>4} ║", Paint(name_color, format!("{:<21}", name)), max);
+        }
+        for (i, cell) in cells.iter().enumerate() {
+            if highlighted == Some(i) {
+                print!("{}{}{}║", style::Invert, Paint(cursor, format!(" {:>w$} ", cell.text, w = self.width - 2)),
+                       style::Reset);
+            } else {
+                let cell_color = if cell.text.is_empty() { None } else { cell.color };
+                print!(" {} ║", Paint(cell_color, format!("{:>w$}", cell.text, w = self.width - 2)));
+            }
+        }
+        println!();
+    }
+}
+
+/// Sums are left blank until they have any points.
+fn points_or_empty(points: u16) -> SlotState {
+    match points {
+        0 => SlotState::Empty,
+        points => SlotState::Scored(points),
+    }
+}
+
+impl Highscore {
+    pub fn print(highscore: &[Entry]) {
+        println!("╔═══════════════════════════════════════════════╗");
+        println!("║ HIGH-SCORE TABLE                              ║");
+        println!("╠══════════════════════════╦════════════╦═══════╣");
+        println!("║ Name                     ║ Date       ║ Score ║");
+        println!("╟──────────────────────────╫────────────╫───────╢");
+
+        for entry in highscore {
+            println!("║ {:<24} ║ {} ║ {:>5} ║", entry.name, entry.time.format("%Y-%m-%d"), entry.score);
+        }
+
+        println!("╚══════════════════════════╩════════════╩═══════╝");
+    }
+}
+
+impl Daily {
+    pub fn print(results: &[(u32, String)], date: NaiveDate) {
+        println!("╔═══════════════════════════════════════════════╗");
+        println!("║ DAILY CHALLENGE{:>30} ║", date.format("%Y-%m-%d").to_string());
+        println!("╠═════════════════════════════════════╦═════════╣");
+        println!("║ Name                                ║   Score ║");
+        println!("╟─────────────────────────────────────╫─────────╢");
+        for (score, name) in results {
+            println!("║ {:<35} ║ {:>7} ║", name, score);
+        }
+        println!("╚═════════════════════════════════════╩═════════╝");
+    }
+}
+
+impl Dice {
+    pub fn print(&self) {
+        let mut rows = [String::new(), String::new(), String::new(),
+                        String::new(), String::new()];
+
+        for (i, &die) in self.current.iter().enumerate() {
+            let dot = if self.to_keep[i] > 0 {
+                Paint(color(|theme| theme.held), '●')
+            } else {
+                Paint(None, '○')
+            };
+            // Which of the seven pip positions are used: top left, top
+            // right, middle left, centre, middle right, bottom left and
+            // bottom right.
+            let pips = match die {
+                1 => [false, false, false, true, false, false, false],
+                2 => [false, true, false, false, false, true, false],
+                3 => [false, true, false, true, false, true, false],
+                4 => [true, true, false, false, false, true, true],
+                5 => [true, true, false, true, false, true, true],
+                6 => [true, true, true, false, true, true, true],
+                _ => [false; 7],
+            };
+            let pip = |i: usize| if pips[i] { dot.to_string() } else { String::from(" ") };
+            let gap = if i > 0 { " " } else { "" };
+
+            rows[0].push_str(&format!("{}╔═══════╗", gap));
+            rows[1].push_str(&format!("{}║ {}   {} ║", gap, pip(0), pip(1)));
+            rows[2].push_str(&format!("{}║ {} {} {} ║", gap, pip(2), pip(3), pip(4)));
+            rows[3].push_str(&format!("{}║ {}   {} ║", gap, pip(5), pip(6)));
+            rows[4].push_str(&format!("{}╚═══════╝", gap));
+        }
+
+        for row in rows.iter() {
+            println!("{}", row);
+        }
+    }
+}
+
+/// Tells whose turn it is when more than one player is playing.
+pub fn print_turn(game: &GameState) {
+    if game.players().len() > 1 {
+        println!("  {}'s turn.", game.players()[game.current_player()].name());
+    }
+}
+
+/// Tells the player how to pick a slot.
+pub fn print_slot_keys(game: &GameState) {
+    let keys = &config::get().keys;
+    println!("  Use {} and press {} to select.", keys.describe_moves(), key_name(Action::Score));
+    if let (true, Some(key)) = (hints_available(game), keys.describe(Action::Hint)) {
+        println!("  Press {} for a hint.", key);
+    }
+}
+
+/// Plays the turn of a computer player, pausing after each step so that it
+/// can be followed on screen.
+pub fn play_bot_turn(game: &mut GameState) -> Result<(), Error> {
+    let player = &game.players()[game.current_player()];
+    let name = player.name().to_string();
+    // Only called for the turn of a computer player.
+    let strategy = player.strategy().expect("not a computer player");
+    let mut rng = game.turn_rng();
+
+    if game.rolls() == 0 {
+        // The first roll of a turn is always there in a game not over.
+        game.roll().unwrap();
+        show_bot_step(game, &format!("{} rolls the dice.", name), None, BOT_PAUSE)?;
+    }
+    while game.rolls_left() > 0 {
+        let keep = strategy.keep(game, &mut rng);
+        if keep.iter().all(|&kept| kept) {
+            break;
+        }
+        for (i, &kept) in keep.iter().enumerate() {
+            if kept != (game.dice().to_keep[i] != 0) {
+                // The dice have been rolled and `keep` has one per die.
+                game.toggle_keep(i).unwrap();
+                show_bot_step(game, &format!("{} picks which dice to keep.", name), None, BOT_STEP)?;
+            }
+        }
+        show_bot_step(game, &format!("{} rerolls.", name), None, BOT_PAUSE)?;
+        // Checked above that there are rolls left.
+        game.roll().unwrap();
+        show_bot_step(game, &format!("{} rolls the dice.", name), None, BOT_PAUSE)?;
+    }
+
+    let category = strategy.choose(game, &mut rng);
+    let label = game.ruleset().label(category);
+    for &cursor in game.ruleset().categories() {
+        show_bot_step(game, &format!("{} picks a slot.", name), Some(cursor), BOT_STEP)?;
+        if cursor == category {
+            break;
+        }
+    }
+    let message = match game.place(category) {
+        Ok(points) => format!("{} scores {} in {}.", name, points, label),
+        Err(_) => {
+            // `choose` picks an open slot, which can be struck out.
+            game.strike(category).unwrap();
+            format!("{} strikes out {}.", name, label)
+        },
+    };
+    show_bot_step(game, &message, None, 2 * BOT_PAUSE)
+}
+
+/// Milliseconds to show each roll and the result of a computer player's turn.
+const BOT_PAUSE: u64 = 900;
+/// Milliseconds between the small steps of a computer player's turn.
+const BOT_STEP: u64 = 150;
+
+fn show_bot_step(game: &GameState, message: &str, cursor: Option<Category>, pause: u64) -> Result<(), Error> {
+    clear_screen();
+    println!("  {}", message);
+    print_game(game, cursor);
+    game.dice().print();
+    stdout().flush()?;
+    thread::sleep(config::get().bot_pause(pause));
+    Ok(())
+}
+
+/// Lets the player toggle which dice to keep until Enter is pressed.
+pub fn select_dice(game: &mut GameState) -> Result<(), Error> {
+    let mut cursor: usize = 0;
+    let mut hint: Option<Vec<String>> = None;
+    loop {
+        clear_screen();
+        print_turn(game);
+        let keys = &config::get().keys;
+        println!("  Use {} and {} to toggle which\n  dice to keep. Then press {} to reroll{}.",
+                 keys.describe_moves(), key_name(Action::Toggle), key_name(Action::Roll),
+                 if game.rolls_left() == 1 { "\n  for the last time" } else { "" });
+        let dice: Vec<String> = (0..game.dice().current.len())
+            .filter_map(|n| keys.describe(Action::Die(n)))
+            .collect();
+        if !dice.is_empty() {
+            println!("  Or press {} to toggle a die directly.", dice.join("/"));
+        }
+        if let (true, Some(category)) = (game.is_forced(), game.next_slot()) {
+            println!("  This turn goes in {}.", game.ruleset().label(category));
+        }
+        if game.ruleset().saves_rolls() {
+            println!("  Rolls left: {} ({} saved). Keep all dice to\n  score now and save the rest.",
+                     game.rolls_left(), game.saved_rolls());
+        }
+        match hint {
+            Some(ref lines) => for line in lines {
+                println!("{}", line);
+            },
+            None => if let (true, Some(key)) = (hints_available(game), keys.describe(Action::Hint)) {
+                println!("  Press {} for a hint.", key);
+            },
+        }
+        if let (true, Some(key)) = (game.undo_limit() > 0, keys.describe(Action::Undo)) {
+            println!("  Press {} to undo a toggle.", key);
+        }
+        print_game(game, None);
+        game.dice().print();
+        if config::get().odds {
+            print_odds(game);
+        }
+        match select_checker(game, &mut cursor)? {
+            DiceSelectStatus::Exit => quit(game),
+            DiceSelectStatus::Complete => break,
+            DiceSelectStatus::Hint => hint = Some(keep_hint(game)),
+            DiceSelectStatus::Incomplete => continue,
+        };
+    }
+    Ok(())
+}
+
+/// Draws the chance of filling each open slot to the right of the score
+/// sheet, ending above the dice, if the terminal is wide enough.
+fn print_odds(game: &GameState) {
+    let column = SheetLayout::new(game.players().len()).inner_width() + 4;
+    let (columns, _) = termion::terminal_size().unwrap_or((0, 0));
+    if usize::from(columns) < column + 26 {
+        return;
+    }
+    let mut chances = game.chances();
+    if game.is_forced() {
+        chances.retain(|&(category, _)| Some(category) == game.next_slot());
+    }
+    let mut lines = vec![
+        format!("Chance to fill with {} {}", game.rolls_left(),
+                if game.rolls_left() == 1 { "roll left" } else { "rolls left" }),
+        String::new(),
+    ];
+    for (category, chance) in chances {
+        let line = format!("{:<18}{:>6.1}%", game.ruleset().label(category), 100.0 * chance);
+        let line_color = if chance == 0.0 { color(|theme| theme.zero) } else { None };
+        lines.push(Paint(line_color, line).to_string());
+    }
+    // Five lines of dice below the last line of the panel.
+    for (i, line) in lines.iter().enumerate() {
+        let up = (5 + lines.len() - i) as u16;
+        print!("{}{}{}{}{}", termion::cursor::Save, termion::cursor::Up(up),
+               termion::cursor::Right(column as u16), line, termion::cursor::Restore);
+    }
+}
+
+fn select_checker(game: &mut GameState, cursor: &mut usize) -> Result<DiceSelectStatus, Error> {
+
+    // Enter raw mode
+    // Get the standard input stream.
+    let stdin = stdin();
+    // Get the standard output stream and go to raw mode.
+    let mut stdout = stdout().into_raw_mode()?;
+    write!(stdout, "{}", termion::cursor::Hide)?;
+
+    let term_size = termion::terminal_size().unwrap_or((151, 38)); // In case of error, set this value
+
+    let bottom_line = term_size.1;
+    let mut left_margin = " ".repeat(10 * *cursor);
+
+    // Flush stdout (i.e. make the output appear).
+    stdout.flush()?;
+    println!("{}{}{}", left_margin, dice_cursor(), termion::cursor::Goto(1, bottom_line -4));
+
+    for c in stdin.keys() {
+        // Clear the current line.
+        write!(stdout, "{}{}", termion::cursor::Goto(1, bottom_line), termion::clear::CurrentLine)?;
+
+        let key = c?;
+        match key {
+            Key::Ctrl('z') => {
+                terminal::suspend(&mut stdout)?;
+                return Ok(DiceSelectStatus::Incomplete);
+            },
+            Key::Ctrl('c') => return Ok(DiceSelectStatus::Exit),
+            _ => (),
+        }
+        match config::get().keys.action(Screen::Dice, key) {
+            Some(Action::Roll) => return Ok(DiceSelectStatus::Complete),
+            Some(Action::Hint) => return Ok(DiceSelectStatus::Hint),
+            Some(Action::Undo) => {
+                if game.undo().is_ok() {
+                    return Ok(DiceSelectStatus::Incomplete);
+                }
+            },
+            Some(Action::Toggle)  => { println!("{}{}{}", left_margin, dice_cursor(), termion::cursor::Goto(1, bottom_line -4));
+                                       game.toggle_keep(*cursor).unwrap();
+                                       return Ok(DiceSelectStatus::Incomplete); },
+            Some(Action::Die(n)) if n < game.dice().current.len() => {
+                game.toggle_keep(n).unwrap();
+                return Ok(DiceSelectStatus::Incomplete);
+            },
+            Some(Action::Left) | Some(Action::Down) => {
+                if *cursor > 0 { *cursor -= 1 };
+                left_margin = " ".repeat(10 * *cursor);
+                println!("{}{}{}", left_margin, dice_cursor(), termion::cursor::Goto(1, bottom_line -4)); },
+            Some(Action::Right) | Some(Action::Up) => {
+                if *cursor < game.dice().current.len() - 1 { *cursor += 1 };
+                left_margin = " ".repeat(10 * *cursor);
+                println!("{}{}{}", left_margin, dice_cursor(), termion::cursor::Goto(1, bottom_line -4)); },
+            _ => continue,
+        }
+
+        // Flush again.
+        stdout.flush()?;
+    }
+
+    // Show the cursor again before we exit.
+    write!(stdout, "{}", termion::cursor::Show)?;
+    Ok(DiceSelectStatus::Incomplete)
+}
+
+/// Lets the player pick a slot to score or strike out until one is used.
+pub fn place_points(game: &mut GameState) -> Result<(), Error> {
+    if game.is_forced() {
+        return place_forced(game);
+    }
+    let mut i: usize = 0;
+    // Whether the next - strikes out the slot when strikes are confirmed.
+    let mut armed = false;
+    loop {
+        print_game(game, Some(game.ruleset().categories()[i]));
+        game.dice().print();
+
+        let status = select_slot(game, &mut i, armed)?;
+        armed = matches!(status, SlotSelectStatus::Invalid | SlotSelectStatus::ConfirmStrike);
+        match status {
+            SlotSelectStatus::Exit => quit(game),
+            SlotSelectStatus::AlreadySelected => { println!("{}", clear::All);
+                                                   println!("  Sorry, you can't use this slot again.");
+                                                   println!("  Press Enter to continue.");
+                                                   print_game(game, Some(game.ruleset().categories()[i]));
+                                                   game.dice().print();
+                                                   let stdin = stdin();
+                                                   let mut stdout = stdout().into_raw_mode()?;
+                                                   write!(stdout, "{}", termion::cursor::Hide)?;
+                                                   for c in stdin.keys() {
+                                                       let key = c?;
+                                                       let action = config::get().keys.action(Screen::Slots, key);
+                                                       match key {
+                                                           Key::Ctrl('z') => {
+                                                               terminal::suspend(&mut stdout)?;
+                                                               break;
+                                                           },
+                                                           Key::Ctrl(c) => if c == 'c' {
+                                                               stdout.suspend_raw_mode()?;
+                                                               quit(game);
+                                                           },
+                                                           Key::Char('\n') => {
+                                                               println!("{}", clear::All);
+                                                               break;
+                                                           },
+                                                           _ if action == Some(Action::Up) || action == Some(Action::Left) => {
+                                                               i = previous_slot(game, i);
+                                                               println!("{}", clear::All);
+                                                               break;
+                                                           },
+                                                           _ if action == Some(Action::Down) || action == Some(Action::Right) => {
+                                                               i = next_slot(game, i);
+                                                               println!("{}", clear::All);
+                                                               break;
+                                                           },
+                                                           _ => { println!("{}", clear::All);
+                                                                  continue;
+                                                           }
+                                                       }
+                                                   }
+                                                   stdout.flush()?;
+            },
+
+            SlotSelectStatus::Complete  => { let category = game.ruleset().categories()[i];
+                                             let draw = |game: &GameState| {
+                                                 println!("{}", clear::All);
+                                                 if let (true, Some(key)) = (game.undo_limit() > 0, config::get().keys.describe(Action::Undo)) {
+                                                     println!("  Selection complete. Press Enter to continue,\n  or {} to undo.", key);
+                                                 } else {
+                                                     println!("  Selection complete. Press Enter to continue.");
+                                                 }
+                                                 print_game(game, Some(category));
+                                                 game.dice().print();
+                                             };
+                                             if !config::get().confirm_placement && game.undo_limit() == 0 {
+                                                 break;
+                                             }
+                                             if wait_for_enter_or_undo(game, &draw)? {
+                                                 println!("{}", clear::All);
+                                                 continue;
+                                             }
+                                             break;
+            },
+            SlotSelectStatus::Incomplete => {
+                println!("{}", clear::All);
+            },
+            SlotSelectStatus::Hint => {
+                println!("{}", clear::All);
+                for line in slot_hint(game) {
+                    println!("{}", line);
+                }
+            },
+            SlotSelectStatus::Invalid => {
+                println!("{}", clear::All);
+                println!("  Invalid selection. Press {} to strike it out", key_name(Action::Strike));
+                println!("  or an arrow key to cancel.");
+            },
+            SlotSelectStatus::ConfirmStrike => {
+                let label = game.ruleset().label(game.ruleset().categories()[i]);
+                println!("{}", clear::All);
+                println!("  Press {} again to strike out {},", key_name(Action::Strike), label);
+                println!("  or an arrow key to cancel.");
+            },
+            SlotSelectStatus::NotAllowed => {
+                println!("{}", clear::All);
+                println!("  The Joker rules require another slot.");
+                println!("  Use {} to pick one.", config::get().keys.describe_moves());
+            },
+        }
+    }
+    println!("{}", clear::All);
+    Ok(())
+}
+
+/// Places the dice in the next open slot, striking it out if they do not
+/// qualify.
+fn place_forced(game: &mut GameState) -> Result<(), Error> {
+    // Only called during a turn, so a slot is still open.
+    let category = game.next_slot().unwrap();
+    let label = game.ruleset().label(category);
+    let message = match game.place(category) {
+        Ok(points) => format!("  {} points in {}. Press Enter to continue.", points, label),
+        Err(_) => {
+            // The slot is open, so it can be struck out.
+            game.strike(category).unwrap();
+            format!("  {} is struck out. Press Enter to continue.", label)
+        },
+    };
+    let draw = |game: &GameState| {
+        println!("{}", clear::All);
+        println!("{}", message);
+        print_game(game, Some(category));
+        game.dice().print();
+    };
+    if config::get().confirm_placement {
+        wait_for_enter(game, &draw)?;
+    }
+    println!("{}", clear::All);
+    Ok(())
+}
+
+/// Draws a screen with `draw` and waits for Enter.
+fn wait_for_enter(game: &GameState, draw: &dyn Fn(&GameState)) -> Result<(), Error> {
+    draw(game);
+    let stdin = stdin();
+    let mut stdout = stdout().into_raw_mode()?;
+    write!(stdout, "{}", termion::cursor::Hide)?;
+    for c in stdin.keys() {
+        match c? {
+            Key::Ctrl('z') => {
+                terminal::suspend(&mut stdout)?;
+                draw(game);
+            },
+            Key::Ctrl(c) => if c == 'c' {
+                stdout.suspend_raw_mode()?;
+                quit(game);
+            },
+            Key::Char('\n') => break,
+            _ => continue,
+        }
+    }
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Draws a screen with `draw` and waits for Enter, or for u to undo the
+/// last action if undo is on. Returns whether it was undone.
+fn wait_for_enter_or_undo(game: &mut GameState, draw: &dyn Fn(&GameState)) -> Result<bool, Error> {
+    draw(game);
+    let stdin = stdin();
+    let mut stdout = stdout().into_raw_mode()?;
+    write!(stdout, "{}", termion::cursor::Hide)?;
+    stdout.flush()?;
+    for c in stdin.keys() {
+        match c? {
+            Key::Ctrl('z') => {
+                terminal::suspend(&mut stdout)?;
+                draw(game);
+            },
+            Key::Ctrl('c') => {
+                stdout.suspend_raw_mode()?;
+                quit(game);
+            },
+            Key::Char('\n') => break,
+            key if config::get().keys.keys(Action::Undo).contains(&key) && game.undo().is_ok() => {
+                return Ok(true);
+            },
+            _ => continue,
+        }
+    }
+    Ok(false)
+}
+
+fn previous_slot(game: &GameState, i: usize) -> usize {
+    if i > 0 { i - 1 } else { game.ruleset().categories().len() - 1 }
+}
+
+fn next_slot(game: &GameState, i: usize) -> usize {
+    if i < game.ruleset().categories().len() - 1 { i + 1 } else { 0 }
+}
+
+/// Moves the cursor `i` over the slots until one is picked. With `armed`,
+/// a strike has been asked for already and pressing strike again goes
+/// ahead with it.
+fn select_slot(game: &mut GameState, i: &mut usize, armed: bool) -> Result<SlotSelectStatus, Error> {
+
+    // Enter raw mode
+    // Get the standard input stream.
+    let stdin = stdin();
+    // Get the standard output stream and go to raw mode.
+    let mut stdout = stdout().into_raw_mode()?;
+    write!(stdout, "{}", termion::cursor::Hide)?;
+    // Flush stdout (i.e. make the output appear).
+    stdout.flush()?;
+
+    for c in stdin.keys() {
+        let key = c?;
+        match key {
+            Key::Ctrl('z') => {
+                terminal::suspend(&mut stdout)?;
+                return Ok(SlotSelectStatus::Incomplete);
+            },
+            Key::Ctrl('c') => return Ok(SlotSelectStatus::Exit),
+            _ => (),
+        }
+        match config::get().keys.action(Screen::Slots, key) {
+            Some(Action::Score) => {
+                let category = game.ruleset().categories()[*i];
+                let status = match game.place(category) {
+                    Ok(_) => SlotSelectStatus::Complete,
+                    Err(GameError::AlreadySelected) => SlotSelectStatus::AlreadySelected,
+                    Err(GameError::NotAllowed) => SlotSelectStatus::NotAllowed,
+                    Err(_) => SlotSelectStatus::Invalid,
+                };
+                stdout.flush()?;
+                return Ok(status);
+            },
+            Some(Action::Strike) => {
+                let category = game.ruleset().categories()[*i];
+                if config::get().confirm_strike && !armed
+                    && game.score().get(category) == Some(SlotState::Empty) {
+                    return Ok(SlotSelectStatus::ConfirmStrike);
+                }
+                match game.strike(category) {
+                    Ok(()) => {
+                        stdout.flush()?;
+                        return Ok(SlotSelectStatus::Complete);
+                    },
+                    Err(GameError::NotAllowed) => return Ok(SlotSelectStatus::NotAllowed),
+                    Err(_) => (),
+                }
+            },
+            Some(Action::Hint) => return Ok(SlotSelectStatus::Hint),
+            Some(Action::Up) | Some(Action::Left) => {
+                *i = previous_slot(game, *i);
+                return Ok(SlotSelectStatus::Incomplete);
+            },
+            Some(Action::Down) | Some(Action::Right) => {
+                *i = next_slot(game, *i);
+                return Ok(SlotSelectStatus::Incomplete);
+            },
+            _ => continue,
+        }
+    }
+
+    // Flush again.
+    stdout.flush()?;
+
+    // Show the cursor again before we exit.
+    write!(stdout, "{}", termion::cursor::Show)?;
+    Ok(SlotSelectStatus::Incomplete)
+}
+
+/// The name of the key for `action`. The config file makes sure there is
+/// one, but a missing one shows as a question mark rather than a panic.
+fn key_name(action: Action) -> String {
+    config::get().keys.describe(action).unwrap_or_else(|| String::from("?"))
+}
+
+/// Saves the game to be resumed later, unless it is over, and exits.
+pub fn quit(game: &GameState) -> ! {
+    let result = save::path().and_then(|path| {
+        if game.is_over() { save::remove(&path) } else { save::store(&path, game) }
+    });
+    if let Err(e) = result {
+        eprintln!("Couldn't save the game: {}", e);
+    }
+    terminal::exit(0);
+}
+
+/// Asks whether to resume the saved game.
+pub fn offer_resume() -> Result<bool, Error> {
+    let prompt = "  There is a saved game. Press r to resume it,\n  or Enter to start a new game.";
+    println!("{}", prompt);
+    let stdin = stdin();
+    let mut stdout = stdout().into_raw_mode()?;
+    write!(stdout, "{}", termion::cursor::Hide)?;
+    stdout.flush()?;
+    for c in stdin.keys() {
+        match c? {
+            Key::Char('r') => return Ok(true),
+            Key::Char('\n') => return Ok(false),
+            Key::Ctrl('z') => {
+                terminal::suspend(&mut stdout)?;
+                stdout.suspend_raw_mode()?;
+                clear_screen();
+                println!("{}", prompt);
+                stdout.activate_raw_mode()?;
+            },
+            Key::Ctrl('c') => terminal::exit(0),
+            _ => continue,
+        }
+    }
+    Ok(false)
+}
+
+/// Whether the solver can give hints in this game.
+pub fn hints_available(game: &GameState) -> bool {
+    !game.is_forced() && Solver::available(game.ruleset())
+}
+
+/// The solver for hints and the optimal bot, telling the player to wait if
+/// it has to be worked out first.
+pub fn hint_solver(game: &GameState) -> Option<&'static Solver> {
+    if !hints_available(game) {
+        return None;
+    }
+    if !Solver::cache_path(game.ruleset()).is_ok_and(|path| path.exists()) {
+        println!("  Working out optimal play. This takes a minute the first time...");
+        let _ = stdout().flush();
+    }
+    Solver::for_ruleset(game.ruleset())
+}
+
+/// How many alternatives a hint lists.
+const HINT_ALTERNATIVES: usize = 4;
+
+/// The best dice to keep and the expected final score of the runners-up.
+fn keep_hint(game: &GameState) -> Vec<String> {
+    let solver = match hint_solver(game) {
+        Some(solver) => solver,
+        None => return vec!["  There are no hints in this game.".to_string()],
+    };
+    let dice = &game.dice().current;
+    let keeps = solver.keep_values(game.score(), dice, game.rolls_left());
+    let mut lines = vec!["  Hint, with the expected final score:".to_string()];
+    for (keep, expected) in keeps.iter().take(HINT_ALTERNATIVES) {
+        let mut kept: Vec<usize> = (0..dice.len()).filter(|&i| keep[i]).map(|i| dice[i]).collect();
+        kept.sort();
+        let kept: Vec<String> = kept.iter().map(|die| die.to_string()).collect();
+        let action = if kept.len() == dice.len() {
+            let (category, _) = solver.slot_values(game.score(), dice)[0];
+            format!("score in {}", game.ruleset().label(category))
+        } else if kept.is_empty() {
+            "reroll all".to_string()
+        } else {
+            format!("keep {}", kept.join(" "))
+        };
+        lines.push(format!("    {:<26}{:>6.1}", action, expected));
+    }
+    lines
+}
+
+/// The best slot to use and the expected final score of the runners-up.
+fn slot_hint(game: &GameState) -> Vec<String> {
+    let solver = match hint_solver(game) {
+        Some(solver) => solver,
+        None => return vec!["  There are no hints in this game.".to_string()],
+    };
+    let mut lines = vec!["  Hint, with the expected final score:".to_string()];
+    for (category, expected) in solver.slot_values(game.score(), &game.dice().current)
+        .into_iter()
+        .take(HINT_ALTERNATIVES) {
+        let action = match game.points(category) {
+            Some(points) => format!("{} for {}", game.ruleset().label(category), points),
+            None => format!("strike {}", game.ruleset().label(category)),
+        };
+        lines.push(format!("    {:<26}{:>6.1}", action, expected));
+    }
+    lines
+}
+
+/// Offers a review of every decision of the game against optimal play.
+pub fn offer_analysis(game: &GameState) -> Result<(), Error> {
+    if !hints_available(game) {
+        return Ok(());
+    }
+    println!("  Press a to see where points were lost, or Enter to exit.");
+    let stdin = stdin();
+    let mut stdout = stdout().into_raw_mode()?;
+    write!(stdout, "{}", termion::cursor::Hide)?;
+    stdout.flush()?;
+    for c in stdin.keys() {
+        match c? {
+            Key::Char('a') => break,
+            Key::Ctrl('z') => {
+                terminal::suspend(&mut stdout)?;
+                stdout.suspend_raw_mode()?;
+                println!("  Press a to see where points were lost, or Enter to exit.");
+                stdout.activate_raw_mode()?;
+            },
+            Key::Char('\n') | Key::Ctrl('c') => {
+                write!(stdout, "{}", termion::cursor::Show)?;
+                return Ok(());
+            },
+            _ => continue,
+        }
+    }
+    stdout.suspend_raw_mode()?;
+    clear_screen();
+    hint_solver(game);
+    wait_for_enter(game, &|game| {
+        clear_screen();
+        print_analysis(game);
+        println!("\n  Press Enter to exit.");
+    })?;
+    write!(stdout, "{}", termion::cursor::Show)?;
+    Ok(())
+}
+
+/// How many of the worst decisions the analysis lists.
+const ANALYSIS_DECISIONS: usize = 12;
+
+fn print_analysis(game: &GameState) {
+    let solver = match hint_solver(game) {
+        Some(solver) => solver,
+        None => return,
+    };
+    let decisions = match analysis::analyze(game) {
+        Some(decisions) => decisions,
+        None => return,
+    };
+    let players = game.players();
+    println!("  ANALYSIS\n");
+    println!("  Optimal play scores {:.1} points on average.", solver.expected(&Score::for_ruleset(game.ruleset())));
+    for (i, player) in players.iter().enumerate() {
+        let lost: f64 = decisions.iter()
+            .filter(|decision| decision.player == i)
+            .map(Decision::loss)
+            .sum();
+        let name = if players.len() == 1 { "You" } else { player.name() };
+        println!("  {} scored {} and lost {:.1} expected points to decisions.",
+                 name, player.score().total(), lost);
+    }
+
+    let mut worst: Vec<&Decision> = decisions.iter()
+        .filter(|decision| decision.loss() >= 0.05)
+        .collect();
+    // Expected values are never NaN.
+    worst.sort_by(|a, b| b.loss().partial_cmp(&a.loss()).unwrap());
+    if worst.is_empty() {
+        println!("\n  Every decision was optimal.");
+        return;
+    }
+    println!("\n  The biggest losses:\n");
+    println!("  {:<20}{:<9}{:<24}{:<24}{:>5}", "Turn", "Dice", "Chosen", "Best", "Loss");
+    for decision in worst.into_iter().take(ANALYSIS_DECISIONS) {
+        let mut when = format!("{}, roll {}", decision.turn, decision.roll);
+        if players.len() > 1 {
+            when = format!("{} {}", players[decision.player].name(), when);
+        }
+        let dice: Vec<String> = decision.dice.iter().map(|die| die.to_string()).collect();
+        println!("  {:<20}{:<9}{:<24}{:<24}{:>5.1}", when, dice.concat(),
+                 describe(game, &decision.chosen, decision.dice.len()),
+                 describe(game, &decision.best, decision.dice.len()), decision.loss());
+    }
+}
+
+fn describe(game: &GameState, choice: &Choice, dice: usize) -> String {
+    match *choice {
+        Choice::Keep(ref kept) if kept.len() == dice => "stop rolling".to_string(),
+        Choice::Keep(ref kept) if kept.is_empty() => "reroll all".to_string(),
+        Choice::Keep(ref kept) => {
+            let kept: Vec<String> = kept.iter().map(|die| die.to_string()).collect();
+            format!("keep {}", kept.join(" "))
+        },
+        Choice::Slot(category, Some(points)) => format!("{} {}", game.ruleset().label(category), points),
+        Choice::Slot(category, None) => format!("strike {}", game.ruleset().label(category)),
+    }
+}
+
+/// Plays back the moves of a recorded game, one at a time.
+pub fn replay(game: &GameState) -> Result<(), Error> {
+    let moves = game.history().len();
+    let mut shown = 0;
+    loop {
+        let state = game.replayed(shown);
+        let mut view = game.replayed(shown);
+        // Show the dice kept for the next roll as they were picked.
+        if let (Some(Move::Roll { kept, .. }), true) = (game.history().get(shown), view.rolls() > 0) {
+            for (i, &kept) in kept.iter().enumerate() {
+                if kept {
+                    view.toggle_keep(i).unwrap();
+                }
+            }
+        }
+        clear_screen();
+        println!("  REPLAY  Move {} of {}", shown, moves);
+        match shown {
+            0 => println!("  The game has not started yet."),
+            _ => println!("  {}", describe_move(&game.replayed(shown - 1), &game.history()[shown - 1])),
+        }
+        println!("  Use the arrow keys to step through the game,\n  and press Enter to exit.");
+        print_game(&state, None);
+        view.dice().print();
+
+        let stdin = stdin();
+        let mut stdout = stdout().into_raw_mode()?;
+        write!(stdout, "{}", termion::cursor::Hide)?;
+        stdout.flush()?;
+        for c in stdin.keys() {
+            match c? {
+                Key::Right | Key::Char(' ') if shown < moves => shown += 1,
+                Key::Left if shown > 0 => shown -= 1,
+                Key::Home => shown = 0,
+                Key::End => shown = moves,
+                Key::Ctrl('z') => terminal::suspend(&mut stdout)?,
+                Key::Char('\n') | Key::Char('q') | Key::Ctrl('c') => {
+                    write!(stdout, "{}", termion::cursor::Show)?;
+                    return Ok(());
+                },
+                _ => continue,
+            }
+            break;
+        }
+    }
+}
+
+/// What `step` did, made in `game`.
+fn describe_move(game: &GameState, step: &Move) -> String {
+    let name = game.players()[game.current_player()].name();
+    let name = if name.is_empty() { "You" } else { name };
+    match *step {
+        Move::Roll { ref dice, .. } => {
+            let dice: Vec<String> = dice.iter().map(|die| die.to_string()).collect();
+            format!("{} rolled {}.", name, dice.join(" "))
+        },
+        Move::Place(category) => format!("{} scored {} in {}.", name,
+                                         game.points(category).unwrap_or(0),
+                                         game.ruleset().label(category)),
+        Move::Strike(category) => format!("{} struck out {}.", name, game.ruleset().label(category)),
+    }
+}
+
+/// Shows the score sheet and the logo until Enter is pressed.
+pub fn welcome(game: &GameState) -> Result<(), Error> {
+    let draw = || {
+        clear_screen();
+        println!("  Press Enter to roll the dice\n  or Ctrl+c at any time to exit.");
+        print_game(game, None);
+        println!("R U S T Y R U S T Y R U S T Y R U S T Y R U S T Y");
+        println!("U       A U       A U       A U       A U       A");
+        println!("S       C S       C S       S T       C S       C");
+        println!("T       H T       H T       H S       H T       H");
+        println!("Y A C H T Y A C H T Y A C H T Y A C H T Y A C H T\n");
+    };
+    draw();
+
+    let stdin = stdin();
+    let mut stdout = stdout().into_raw_mode()?;
+    write!(stdout, "{}", termion::cursor::Hide)?;
+    for c in stdin.keys() {
+        match c? {
+            Key::Char('\n') => break,
+            Key::Ctrl('z') => {
+                terminal::suspend(&mut stdout)?;
+                stdout.suspend_raw_mode()?;
+                draw();
+                stdout.activate_raw_mode()?;
+            },
+            Key::Ctrl(c) => if c == 'c' {
+                terminal::exit(0);
+            },
+            _ => continue,
+        }
+    }
+
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Reads a name of 1 to 24 characters from stdin. Running out of input
+/// before one is given is an error.
+pub fn ask_name(prompt: &str) -> Result<String, Error> {
+    let mut name = String::new();
+    loop {
+        println!("{}", prompt);
+        if stdin().read_line(&mut name)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "no name was given").into());
+        }
+        name.pop(); // remove trailing newline
+        let namelen = name.chars().count();
+        if namelen > 24 {
+            println!("Too long! Max length is 24 characters.\n");
+            name.clear();
+        } else if namelen > 0 {
+            return Ok(name);
+        }
+    }
+}
+
+pub fn clear_screen() {
+    println!("{}", clear::All);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Yatzy;
+
+    #[test]
+    fn replay_describes_each_move() {
+        let mut game = GameState::with_players(Box::new(Yatzy), vec!["Albin".to_string()]);
+        let roll = Move::Roll { kept: vec![false; 5], dice: vec![2, 2, 5, 5, 5] };
+        assert_eq!(describe_move(&game, &roll), "Albin rolled 2 2 5 5 5.");
+        game.apply(&roll).unwrap();
+        assert_eq!(describe_move(&game, &Move::Place(Category::FullHouse)), "Albin scored 19 in Full House.");
+        assert_eq!(describe_move(&game, &Move::Strike(Category::Yatzy)), "Albin struck out Yatzy.");
+        assert_eq!(describe_move(&GameState::new(), &roll), "You rolled 2 2 5 5 5.");
+    }
+}